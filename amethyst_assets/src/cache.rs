@@ -1,4 +1,8 @@
-use std::{borrow::Borrow, hash::Hash};
+use std::{
+    borrow::Borrow,
+    hash::Hash,
+    time::{Duration, Instant},
+};
 
 use derivative::Derivative;
 use fnv::FnvHashMap;
@@ -7,10 +11,21 @@ use crate::{Handle, WeakHandle};
 
 /// A simple cache for asset handles of type `A`.
 /// This stores `WeakHandle`, so it doesn't keep the assets alive.
+///
+/// The number of entries and the time they are kept can be limited with `with_max_entries` and
+/// `with_time_to_live`. Expired entries are no longer returned, but are only removed on insertion
+/// or when calling `clear_expired`.
 #[derive(Derivative)]
 #[derivative(Default(bound = ""))]
 pub struct Cache<A> {
-    map: FnvHashMap<String, WeakHandle<A>>,
+    map: FnvHashMap<String, CacheEntry<A>>,
+    max_entries: Option<usize>,
+    time_to_live: Option<Duration>,
+}
+
+struct CacheEntry<A> {
+    handle: WeakHandle<A>,
+    inserted: Instant,
 }
 
 impl<A> Cache<A>
@@ -22,9 +37,40 @@ where
         Default::default()
     }
 
+    /// Limits the number of cached handles. When inserting into a full cache, dead and expired
+    /// handles are removed first, then the oldest ones. A cache limited to 0 entries stores
+    /// nothing.
+    pub fn with_max_entries(mut self, max_entries: usize) -> Self {
+        self.max_entries = Some(max_entries);
+        self
+    }
+
+    /// Expires cached handles `time_to_live` after they were inserted.
+    pub fn with_time_to_live(mut self, time_to_live: Duration) -> Self {
+        self.time_to_live = Some(time_to_live);
+        self
+    }
+
     /// Inserts an asset with a given `key` and returns the old value (if any).
     pub fn insert<K: Into<String>>(&mut self, key: K, asset: &Handle<A>) -> Option<WeakHandle<A>> {
-        self.map.insert(key.into(), asset.downgrade())
+        let key = key.into();
+        if let Some(max_entries) = self.max_entries {
+            if max_entries == 0 {
+                return None;
+            }
+            if !self.map.contains_key(&key) && self.map.len() >= max_entries {
+                self.clear_expired();
+                while self.map.len() >= max_entries {
+                    self.remove_oldest();
+                }
+            }
+        }
+
+        let entry = CacheEntry {
+            handle: asset.downgrade(),
+            inserted: Instant::now(),
+        };
+        self.map.insert(key, entry).map(|entry| entry.handle)
     }
 
     /// Retrieves an asset handle using a given `key`.
@@ -33,16 +79,159 @@ where
         K: ?Sized + Hash + Eq,
         String: Borrow<K>,
     {
-        self.map.get(key).and_then(WeakHandle::upgrade)
+        self.map
+            .get(key)
+            .filter(|entry| !self.is_expired(entry))
+            .and_then(|entry| entry.handle.upgrade())
+    }
+
+    /// Returns the number of cached handles, including dead and expired ones.
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Returns true if nothing is cached.
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// Keeps only the cached handles for which `f` returns true.
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&str, &WeakHandle<A>) -> bool,
+    {
+        self.map.retain(|key, entry| f(key, &entry.handle));
     }
 
     /// Deletes all cached handles which are invalid.
     pub fn clear_dead<F>(&mut self) {
-        self.map.retain(|_, h| !h.is_dead());
+        self.map.retain(|_, entry| !entry.handle.is_dead());
+    }
+
+    /// Deletes all cached handles which are invalid or have expired.
+    pub fn clear_expired(&mut self) {
+        let time_to_live = self.time_to_live;
+        self.map.retain(|_, entry| {
+            !entry.handle.is_dead()
+                && time_to_live.map_or(true, |ttl| entry.inserted.elapsed() < ttl)
+        });
     }
 
     /// Clears all values.
     pub fn clear_all(&mut self) {
         self.map.clear();
     }
+
+    fn is_expired(&self, entry: &CacheEntry<A>) -> bool {
+        self.time_to_live
+            .map_or(false, |ttl| entry.inserted.elapsed() >= ttl)
+    }
+
+    fn remove_oldest(&mut self) {
+        let oldest = self
+            .map
+            .iter()
+            .min_by_key(|(_, entry)| entry.inserted)
+            .map(|(key, _)| key.clone());
+        if let Some(key) = oldest {
+            self.map.remove(&key);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{thread, time::Duration};
+
+    use amethyst_core::specs::prelude::VecStorage;
+
+    use crate::{Asset, AssetStorage, Handle};
+
+    use super::Cache;
+
+    #[derive(Clone)]
+    struct TestAsset;
+
+    impl Asset for TestAsset {
+        fn name() -> &'static str {
+            "TEST"
+        }
+        type Data = ();
+        type HandleStorage = VecStorage<Handle<Self>>;
+    }
+
+    #[test]
+    fn evicts_oldest_entry_when_full() {
+        let storage = AssetStorage::<TestAsset>::new();
+        let handles: Vec<_> = (0..3).map(|_| storage.allocate()).collect();
+        let mut cache = Cache::new().with_max_entries(2);
+
+        cache.insert("a", &handles[0]);
+        thread::sleep(Duration::from_millis(1));
+        cache.insert("b", &handles[1]);
+        thread::sleep(Duration::from_millis(1));
+        cache.insert("c", &handles[2]);
+
+        assert_eq!(cache.len(), 2);
+        assert!(cache.get("a").is_none());
+        assert_eq!(cache.get("b"), Some(handles[1].clone()));
+        assert_eq!(cache.get("c"), Some(handles[2].clone()));
+    }
+
+    #[test]
+    fn replacing_entry_in_full_cache_keeps_others() {
+        let storage = AssetStorage::<TestAsset>::new();
+        let handles: Vec<_> = (0..3).map(|_| storage.allocate()).collect();
+        let mut cache = Cache::new().with_max_entries(2);
+
+        cache.insert("a", &handles[0]);
+        cache.insert("b", &handles[1]);
+        assert!(cache.insert("a", &handles[2]).is_some());
+
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.get("a"), Some(handles[2].clone()));
+        assert_eq!(cache.get("b"), Some(handles[1].clone()));
+    }
+
+    #[test]
+    fn zero_max_entries_stores_nothing() {
+        let storage = AssetStorage::<TestAsset>::new();
+        let handle = storage.allocate();
+        let mut cache = Cache::new().with_max_entries(0);
+
+        assert!(cache.insert("a", &handle).is_none());
+        assert!(cache.is_empty());
+        assert!(cache.get("a").is_none());
+    }
+
+    #[test]
+    fn expired_entries_are_not_returned() {
+        let storage = AssetStorage::<TestAsset>::new();
+        let handle = storage.allocate();
+        let mut cache = Cache::new().with_time_to_live(Duration::from_millis(10));
+
+        cache.insert("a", &handle);
+        assert_eq!(cache.get("a"), Some(handle.clone()));
+
+        thread::sleep(Duration::from_millis(20));
+        assert!(cache.get("a").is_none());
+        assert_eq!(cache.len(), 1);
+
+        cache.clear_expired();
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn dead_entries_are_cleared() {
+        let storage = AssetStorage::<TestAsset>::new();
+        let handle = storage.allocate();
+        let mut cache = Cache::new();
+
+        cache.insert("a", &handle);
+        drop(handle);
+        assert!(cache.get("a").is_none());
+
+        cache.clear_expired();
+        assert!(cache.is_empty());
+    }
 }
@@ -4,6 +4,11 @@ use crate::{Asset, SimpleFormat};
 use amethyst_error::{format_err, Error, ResultExt};
 
 /// Format for loading from Ron files.
+///
+/// Parse errors point at the offending line.
+///
+/// There is no option to reject unknown fields: they are ignored unless the data type is
+/// annotated with `#[serde(deny_unknown_fields)]`.
 #[derive(Default, Clone, Debug, Serialize, Deserialize)]
 pub struct RonFormat;
 
@@ -20,8 +25,10 @@ where
         let mut d = Deserializer::from_bytes(&bytes)
             .with_context(|_| format_err!("Failed deserializing Ron file"))?;
         let val = T::Data::deserialize(&mut d)
+            .map_err(|e| ron_error(&bytes, e))
             .with_context(|_| format_err!("Failed parsing Ron file"))?;
         d.end()
+            .map_err(|e| ron_error(&bytes, e))
             .with_context(|_| format_err!("Failed parsing Ron file"))?;
 
         Ok(val)
@@ -29,6 +36,11 @@ where
 }
 
 /// Format for loading from Json files.
+///
+/// Errors point at the offending line.
+///
+/// There is no option to reject unknown fields: they are ignored unless the data type is
+/// annotated with `#[serde(deny_unknown_fields)]`.
 #[cfg(feature = "json")]
 #[derive(Default, Clone, Debug)]
 pub struct JsonFormat;
@@ -46,10 +58,127 @@ where
         use serde_json::de::Deserializer;
         let mut d = Deserializer::from_slice(&bytes);
         let val = T::Data::deserialize(&mut d)
+            .map_err(|e| json_error(&bytes, e))
             .with_context(|_| format_err!("Failed deserializing Json file"))?;
         d.end()
+            .map_err(|e| json_error(&bytes, e))
             .with_context(|_| format_err!("Failed deserializing Json file"))?;
 
         Ok(val)
     }
 }
+
+/// Adds the location of a Ron parse error and the offending line to the error message.
+fn ron_error(bytes: &[u8], error: ron::de::Error) -> Error {
+    match error {
+        ron::de::Error::Parser(_, ref position) => format_err!(
+            "{} at line {}, column {}\n{}",
+            error,
+            position.line,
+            position.col,
+            source_snippet(bytes, position.line, position.col),
+        ),
+        error => Error::new(error),
+    }
+}
+
+/// Adds the offending line of a Json error to the error message.
+#[cfg(feature = "json")]
+fn json_error(bytes: &[u8], error: serde_json::Error) -> Error {
+    format_err!(
+        "{}\n{}",
+        error,
+        source_snippet(bytes, error.line(), error.column())
+    )
+}
+
+/// Returns line `line` of `bytes`, followed by a marker below column `column`.
+/// Both are counted from 1.
+fn source_snippet(bytes: &[u8], line: usize, column: usize) -> String {
+    let text = String::from_utf8_lossy(bytes);
+    // Unlike `lines`, `split` keeps the empty line after a trailing newline, where errors at the
+    // end of the input are reported.
+    match text.split('\n').nth(line.saturating_sub(1)) {
+        Some(source) => format!(
+            "{}\n{}^",
+            source.trim_end_matches('\r'),
+            " ".repeat(column.saturating_sub(1))
+        ),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "json")]
+    use super::json_error;
+    use super::ron_error;
+
+    fn ron_message(text: &str) -> String {
+        let error = ron::de::from_str::<Vec<u32>>(text).unwrap_err();
+        ron_error(text.as_bytes(), error).to_string()
+    }
+
+    #[cfg(feature = "json")]
+    fn json_message(text: &str) -> String {
+        let error = serde_json::from_str::<Vec<u32>>(text).unwrap_err();
+        json_error(text.as_bytes(), error).to_string()
+    }
+
+    #[test]
+    fn ron_error_on_first_line() {
+        let message = ron_message("[1, x]");
+        assert!(
+            message.ends_with(" at line 1, column 5\n[1, x]\n    ^"),
+            "{}",
+            message
+        );
+    }
+
+    #[test]
+    fn ron_error_on_later_line() {
+        let message = ron_message("[\n  1,\n  x,\n]");
+        assert!(
+            message.ends_with(" at line 3, column 3\n  x,\n  ^"),
+            "{}",
+            message
+        );
+    }
+
+    #[test]
+    fn ron_error_at_end_of_input() {
+        let message = ron_message("[1, 2");
+        assert!(
+            message.ends_with(" at line 1, column 6\n[1, 2\n     ^"),
+            "{}",
+            message
+        );
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn json_error_on_first_line() {
+        assert_eq!(
+            json_message("[1,]"),
+            "trailing comma at line 1 column 4\n[1,]\n   ^"
+        );
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn json_error_on_later_line() {
+        assert_eq!(
+            json_message("[\r\n  1,\r\n  x\r\n]"),
+            "expected value at line 3 column 3\n  x\n  ^"
+        );
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn json_error_at_end_of_input() {
+        assert_eq!(
+            json_message("[\n  1,\n"),
+            "EOF while parsing a value at line 3 column 0\n\n^"
+        );
+    }
+}
@@ -11,6 +11,10 @@
 
 #[cfg(feature = "json")]
 pub use crate::formats::JsonFormat;
+#[cfg(feature = "http")]
+pub use crate::source::HttpSource;
+#[cfg(feature = "pack")]
+pub use crate::source::{PackSource, PackWriter};
 pub use crate::{
     asset::{Asset, Format, FormatValue, SimpleFormat, AssetUUID},
     cache::Cache,
@@ -20,8 +24,8 @@ pub use crate::{
     prefab::{AssetPrefab, Prefab, PrefabData, PrefabLoader, PrefabLoaderSystem},
     progress::{Completion, Progress, ProgressCounter, Tracker},
     reload::{HotReloadBundle, HotReloadStrategy, HotReloadSystem, Reload, SingleFile},
-    source::{Directory, Source},
-    storage::{AssetStorage, Handle, ProcessingState, Processor, WeakHandle},
+    source::{Directory, FallbackSource, Source},
+    storage::{AssetStorage, AssetStorageEvent, Handle, ProcessingState, Processor, WeakHandle},
 };
 
 mod asset;
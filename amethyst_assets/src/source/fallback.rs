@@ -0,0 +1,136 @@
+use fnv::FnvHashMap;
+use log::debug;
+use parking_lot::Mutex;
+
+#[cfg(feature = "profiler")]
+use thread_profiler::profile_scope;
+
+use amethyst_error::{format_err, Error, ResultExt};
+
+use crate::{error, source::Source};
+
+/// Source trying a list of other sources in order.
+///
+/// Every asset is loaded from the first source that provides it, so earlier sources override
+/// later ones. This allows e.g. a patch directory to override the assets shipped with a game.
+///
+/// The source each asset was loaded from is remembered, so its modification time is checked in
+/// that same source.
+#[derive(Default)]
+pub struct FallbackSource {
+    sources: Vec<Box<dyn Source>>,
+    loaded_from: Mutex<FnvHashMap<String, usize>>,
+}
+
+impl FallbackSource {
+    /// Creates a new fallback source without any sources.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Adds a source, which is tried after all sources added before.
+    pub fn with_source<S>(mut self, source: S) -> Self
+    where
+        S: Source,
+    {
+        self.sources.push(Box::new(source));
+        self
+    }
+
+    /// Returns the index of the source `path` was last loaded from, or `None` if it wasn't
+    /// loaded yet.
+    pub fn source_of(&self, path: &str) -> Option<usize> {
+        self.loaded_from.lock().get(path).cloned()
+    }
+
+    fn load_from_first<T, F>(&self, path: &str, f: F) -> Result<T, Error>
+    where
+        F: FnMut(&dyn Source) -> Result<T, Error>,
+    {
+        let (index, value) = self.first_success(path, f)?;
+        self.loaded_from.lock().insert(path.to_owned(), index);
+        Ok(value)
+    }
+
+    fn first_success<T, F>(&self, path: &str, mut f: F) -> Result<(usize, T), Error>
+    where
+        F: FnMut(&dyn Source) -> Result<T, Error>,
+    {
+        let mut last_error = None;
+        for (index, source) in self.sources.iter().enumerate() {
+            match f(source.as_ref()) {
+                Ok(value) => {
+                    debug!("Found {:?} in fallback source {}", path, index);
+                    return Ok((index, value));
+                }
+                Err(e) => last_error = Some(e),
+            }
+        }
+
+        match last_error {
+            Some(e) => Err(e)
+                .with_context(|_| format_err!("None of the sources provides {:?}", path))
+                .with_context(|_| error::Error::Source),
+            None => Err(format_err!("Fallback source without any sources"))
+                .with_context(|_| error::Error::Source),
+        }
+    }
+}
+
+impl Source for FallbackSource {
+    fn modified(&self, path: &str) -> Result<u64, Error> {
+        #[cfg(feature = "profiler")]
+        profile_scope!("fallback_modified_asset");
+
+        match self.source_of(path) {
+            Some(index) => self.sources[index].modified(path),
+            None => self
+                .first_success(path, |source| source.modified(path))
+                .map(|(_, modified)| modified),
+        }
+    }
+
+    fn load(&self, path: &str) -> Result<Vec<u8>, Error> {
+        #[cfg(feature = "profiler")]
+        profile_scope!("fallback_load_asset");
+
+        self.load_from_first(path, |source| source.load(path))
+    }
+
+    fn load_with_metadata(&self, path: &str) -> Result<(Vec<u8>, u64), Error> {
+        #[cfg(feature = "profiler")]
+        profile_scope!("fallback_load_asset_with_metadata");
+
+        // Take the modification time from the same source the bytes come from.
+        self.load_from_first(path, |source| source.load_with_metadata(path))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::Path;
+
+    use crate::source::{Directory, Source};
+
+    use super::FallbackSource;
+
+    #[test]
+    fn loads_from_first_source_providing_asset() {
+        let assets_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests");
+        let source = FallbackSource::new()
+            .with_source(Directory::new(assets_dir.join("missing")))
+            .with_source(Directory::new(assets_dir.join("assets")));
+
+        assert_eq!(source.source_of("subdir/asset"), None);
+        assert_eq!(
+            "data".as_bytes().to_vec(),
+            source
+                .load("subdir/asset")
+                .expect("Failed to load tests/assets/subdir/asset")
+        );
+        assert_eq!(source.source_of("subdir/asset"), Some(1));
+        assert!(source.modified("subdir/asset").is_ok());
+        assert!(source.load("subdir/missing").is_err());
+        assert_eq!(source.source_of("subdir/missing"), None);
+    }
+}
@@ -0,0 +1,265 @@
+use std::{
+    fs,
+    io::Read,
+    path::{Component, Path, PathBuf},
+    time::Duration,
+};
+
+use log::{debug, warn};
+use reqwest::{
+    header::{ETAG, IF_NONE_MATCH},
+    Client, StatusCode, Url,
+};
+
+#[cfg(feature = "profiler")]
+use thread_profiler::profile_scope;
+
+use amethyst_error::{format_err, Error, ResultExt};
+
+use crate::{error, source::Source};
+
+/// Source downloading assets from a web server over HTTP or HTTPS.
+///
+/// Asset paths are appended to the base url, so with a base url of
+/// `https://cdn.example.com/assets` the path `texture/logo.png` is downloaded from
+/// `https://cdn.example.com/assets/texture/logo.png`. Each path segment is percent-encoded, so
+/// names containing spaces, `#` or `?` are requested as they are named on disk.
+///
+/// With a cache directory, downloaded assets are stored on disk together with the `ETag` the
+/// server sent for them. Later loads only download the asset again if the server reports that
+/// it changed, and fall back to the cached copy if the server can't be reached.
+///
+/// Remote assets never report a modification, so they are not hot reloaded.
+///
+/// Requires the `http` feature.
+#[derive(Debug)]
+pub struct HttpSource {
+    base_url: Url,
+    client: Client,
+    cache: Option<PathBuf>,
+}
+
+impl HttpSource {
+    /// Creates a new http source downloading from `base_url`. Requests taking longer than
+    /// `timeout` fail. Returns an error if `base_url` is not a valid base url.
+    pub fn new<S>(base_url: S, timeout: Duration) -> Result<Self, Error>
+    where
+        S: AsRef<str>,
+    {
+        let base_url = base_url.as_ref();
+        let base_url = Url::parse(base_url)
+            .ok()
+            .filter(|url| !url.cannot_be_a_base())
+            .ok_or_else(|| format_err!("Invalid base url {:?}", base_url))?;
+        let client = Client::builder()
+            .timeout(timeout)
+            .build()
+            .with_context(|_| format_err!("Failed to create http client"))?;
+
+        Ok(HttpSource {
+            base_url,
+            client,
+            cache: None,
+        })
+    }
+
+    /// Stores downloaded assets in the given directory.
+    pub fn with_cache<P>(mut self, directory: P) -> Self
+    where
+        P: Into<PathBuf>,
+    {
+        self.cache = Some(directory.into());
+        self
+    }
+
+    fn url(&self, path: &str) -> Url {
+        let mut url = self.base_url.clone();
+        url.path_segments_mut()
+            .expect("Unreachable: Base urls are checked in `HttpSource::new`")
+            .pop_if_empty()
+            .extend(path.split('/').filter(|segment| !segment.is_empty()));
+        url
+    }
+
+    // Paths leaving the cache directory are rejected, so downloads can't overwrite other files.
+    fn cache_path(&self, path: &str) -> Result<Option<PathBuf>, Error> {
+        let cache = match self.cache {
+            Some(ref cache) => cache,
+            None => return Ok(None),
+        };
+        let mut cache_path = cache.clone();
+        for component in Path::new(path).components() {
+            match component {
+                Component::Normal(name) => cache_path.push(name),
+                Component::CurDir => {}
+                Component::ParentDir | Component::RootDir | Component::Prefix(_) => {
+                    return Err(format_err!(
+                        "Asset path {:?} leaves the cache directory",
+                        path
+                    ));
+                }
+            }
+        }
+        Ok(Some(cache_path))
+    }
+
+    fn download(&self, path: &str, cache_path: Option<&Path>) -> Result<Vec<u8>, Error> {
+        let url = self.url(path);
+        let etag_path = cache_path.map(|p| p.with_extension(etag_extension(p)));
+
+        let mut request = self.client.get(url.clone());
+        if let Some(etag) = etag_path
+            .as_ref()
+            .filter(|_| cache_path.map_or(false, Path::exists))
+            .and_then(|p| fs::read_to_string(p).ok())
+        {
+            request = request.header(IF_NONE_MATCH, etag);
+        }
+
+        let response = request
+            .send()
+            .with_context(|_| format_err!("Failed to request {:?}", url))?;
+        if response.status() == StatusCode::NOT_MODIFIED {
+            if let Some(cache_path) = cache_path {
+                debug!("{:?} is unchanged, using cached copy", url);
+                return fs::read(cache_path)
+                    .with_context(|_| format_err!("Failed to read cached file {:?}", cache_path));
+            }
+        }
+        let mut response = response
+            .error_for_status()
+            .with_context(|_| format_err!("Server failed to provide {:?}", url))?;
+
+        let mut bytes = Vec::new();
+        response
+            .read_to_end(&mut bytes)
+            .with_context(|_| format_err!("Failed to download {:?}", url))?;
+
+        if let (Some(cache_path), Some(etag_path)) = (cache_path, etag_path) {
+            let etag = response
+                .headers()
+                .get(ETAG)
+                .and_then(|etag| etag.to_str().ok());
+            if let Err(e) = store(cache_path, &etag_path, &bytes, etag) {
+                warn!("Failed to cache {:?}: {}", url, e);
+            }
+        }
+
+        Ok(bytes)
+    }
+}
+
+impl Source for HttpSource {
+    fn modified(&self, _path: &str) -> Result<u64, Error> {
+        Ok(0)
+    }
+
+    fn load(&self, path: &str) -> Result<Vec<u8>, Error> {
+        #[cfg(feature = "profiler")]
+        profile_scope!("http_load_asset");
+
+        let cache_path = self
+            .cache_path(path)
+            .with_context(|_| error::Error::Source)?;
+        match self.download(path, cache_path.as_ref().map(PathBuf::as_path)) {
+            Ok(bytes) => Ok(bytes),
+            Err(e) => match cache_path.filter(|p| p.exists()) {
+                Some(cache_path) => {
+                    warn!("{}, using cached copy {:?}", e, cache_path);
+                    fs::read(&cache_path)
+                        .with_context(|_| {
+                            format_err!("Failed to read cached file {:?}", cache_path)
+                        })
+                        .with_context(|_| error::Error::Source)
+                }
+                None => Err(e).with_context(|_| error::Error::Source),
+            },
+        }
+    }
+}
+
+// The `ETag` of a cached file is stored next to it, with `.etag` appended to its extension.
+fn etag_extension(path: &Path) -> String {
+    match path.extension() {
+        Some(extension) => format!("{}.etag", extension.to_string_lossy()),
+        None => "etag".to_owned(),
+    }
+}
+
+fn store(path: &Path, etag_path: &Path, bytes: &[u8], etag: Option<&str>) -> Result<(), Error> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, bytes)?;
+    match etag {
+        Some(etag) => fs::write(etag_path, etag)?,
+        None => {
+            let _ = fs::remove_file(etag_path);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use std::{path::Path, time::Duration};
+
+    use super::{etag_extension, HttpSource};
+
+    #[test]
+    fn joins_urls() {
+        let source = HttpSource::new("https://example.com/assets/", Duration::from_secs(1))
+            .expect("Failed to create http source");
+
+        assert_eq!(
+            source.url("texture/logo.png").as_str(),
+            "https://example.com/assets/texture/logo.png"
+        );
+        assert_eq!(
+            source.url("/texture//logo.png").as_str(),
+            "https://example.com/assets/texture/logo.png"
+        );
+    }
+
+    #[test]
+    fn encodes_path_segments() {
+        let source = HttpSource::new("https://example.com/assets", Duration::from_secs(1))
+            .expect("Failed to create http source");
+
+        assert_eq!(
+            source.url("my textures/logo #1?.png").as_str(),
+            "https://example.com/assets/my%20textures/logo%20%231%3F.png"
+        );
+        assert_eq!(
+            source.url("100%.png").as_str(),
+            "https://example.com/assets/100%25.png"
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_base_urls() {
+        assert!(HttpSource::new("example.com/assets", Duration::from_secs(1)).is_err());
+        assert!(HttpSource::new("mailto:assets@example.com", Duration::from_secs(1)).is_err());
+    }
+
+    #[test]
+    fn cache_paths_stay_in_cache_directory() {
+        let source = HttpSource::new("https://example.com/assets", Duration::from_secs(1))
+            .expect("Failed to create http source")
+            .with_cache("cache");
+
+        assert_eq!(
+            source.cache_path("texture/./logo.png").unwrap(),
+            Some(Path::new("cache/texture/logo.png").to_path_buf())
+        );
+        assert!(source.cache_path("../../outside").is_err());
+        assert!(source.cache_path("texture/../../outside").is_err());
+        assert!(source.cache_path("/etc/outside").is_err());
+    }
+
+    #[test]
+    fn etag_is_stored_next_to_cached_file() {
+        assert_eq!(etag_extension(Path::new("cache/logo.png")), "png.etag");
+        assert_eq!(etag_extension(Path::new("cache/logo")), "etag");
+    }
+}
@@ -1,11 +1,21 @@
 use amethyst_error::Error;
 
-pub use self::dir::Directory;
+pub use self::{dir::Directory, fallback::FallbackSource};
+
+#[cfg(feature = "http")]
+pub use self::http::HttpSource;
+#[cfg(feature = "pack")]
+pub use self::pack::{PackSource, PackWriter};
 
 #[cfg(feature = "profiler")]
 use thread_profiler::profile_scope;
 
 mod dir;
+mod fallback;
+#[cfg(feature = "http")]
+mod http;
+#[cfg(feature = "pack")]
+mod pack;
 
 /// A trait for asset sources, which provides
 /// methods for loading bytes.
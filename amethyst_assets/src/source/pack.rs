@@ -0,0 +1,358 @@
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{Read, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+};
+
+#[cfg(feature = "profiler")]
+use thread_profiler::profile_scope;
+
+use amethyst_error::{format_err, Error, ResultExt};
+
+use crate::{error, source::Source};
+
+// A pack starts with a header and ends with a footer pointing to the index of its entries:
+//
+// header: b"AMPK", version: u32
+// entries: the (possibly compressed) bytes of every entry
+// index: for every entry: path length: u32, path, offset: u64, stored length: u64,
+//        size: u64, compression: u8
+// footer: index offset: u64, entry count: u32
+//
+// All integers are little endian.
+const MAGIC: &[u8; 4] = b"AMPK";
+const VERSION: u32 = 1;
+const HEADER_LEN: u64 = 8;
+const FOOTER_LEN: i64 = 12;
+// An index entry without its path: path length, offset, stored length, size and compression.
+const MIN_INDEX_ENTRY_LEN: u64 = 4 + 8 + 8 + 8 + 1;
+
+const STORED: u8 = 0;
+const LZ4: u8 = 1;
+
+#[derive(Debug)]
+struct Entry {
+    offset: u64,
+    stored_len: u64,
+    size: u64,
+    compression: u8,
+}
+
+/// Source reading assets out of a single pack file, written with `PackWriter`.
+///
+/// Only the index of the pack is read when it is opened. Each asset is read and, if it was
+/// compressed, decompressed when it is loaded.
+///
+/// Packs are meant for shipping builds, so assets in them never report a modification and are
+/// not hot reloaded.
+#[derive(Debug)]
+pub struct PackSource {
+    path: PathBuf,
+    entries: HashMap<String, Entry>,
+}
+
+impl PackSource {
+    /// Opens the pack at `path` and reads its index.
+    pub fn open<P>(path: P) -> Result<Self, Error>
+    where
+        P: Into<PathBuf>,
+    {
+        let path = path.into();
+        let entries = read_index(&path)
+            .with_context(|_| format_err!("Failed to read pack index of {:?}", path))?;
+
+        Ok(PackSource { path, entries })
+    }
+
+    /// Iterates over the paths of all assets in the pack.
+    pub fn paths(&self) -> impl Iterator<Item = &str> {
+        self.entries.keys().map(String::as_str)
+    }
+
+    fn entry(&self, path: &str) -> Result<&Entry, Error> {
+        self.entries
+            .get(path)
+            .ok_or_else(|| format_err!("{:?} is not in pack {:?}", path, self.path))
+    }
+}
+
+impl Source for PackSource {
+    fn modified(&self, path: &str) -> Result<u64, Error> {
+        self.entry(path).map(|_| 0)
+    }
+
+    fn load(&self, path: &str) -> Result<Vec<u8>, Error> {
+        #[cfg(feature = "profiler")]
+        profile_scope!("pack_load_asset");
+
+        let entry = self.entry(path).with_context(|_| error::Error::Source)?;
+        read_entry(&self.path, entry)
+            .with_context(|_| format_err!("Failed to read {:?} from pack {:?}", path, self.path))
+            .with_context(|_| error::Error::Source)
+    }
+}
+
+/// Writes a pack file, which can be read with `PackSource`.
+///
+/// ## Example
+///
+/// ```rust,no_run
+/// use std::fs::File;
+///
+/// use amethyst_assets::PackWriter;
+///
+/// let logo = std::fs::read("assets/texture/logo.png").unwrap();
+///
+/// let mut writer = PackWriter::new(File::create("assets.pack").unwrap()).unwrap();
+/// writer.add("texture/logo.png", &logo, false).unwrap();
+/// writer.finish().unwrap();
+/// ```
+#[derive(Debug)]
+pub struct PackWriter<W: Write> {
+    writer: W,
+    offset: u64,
+    index: Vec<(String, Entry)>,
+}
+
+impl<W: Write> PackWriter<W> {
+    /// Starts a new pack, writing its header.
+    pub fn new(mut writer: W) -> Result<Self, Error> {
+        writer.write_all(MAGIC)?;
+        writer.write_all(&VERSION.to_le_bytes())?;
+
+        Ok(PackWriter {
+            writer,
+            offset: HEADER_LEN,
+            index: Vec::new(),
+        })
+    }
+
+    /// Adds an asset under `path`, which should use `/` as separator. If `compress` is true, the
+    /// asset is compressed unless that doesn't make it smaller.
+    ///
+    /// Adding a path twice replaces the earlier asset.
+    pub fn add(&mut self, path: &str, bytes: &[u8], compress: bool) -> Result<(), Error> {
+        let compressed = if compress {
+            Some(lz4_flex::block::compress(bytes)).filter(|c| c.len() < bytes.len())
+        } else {
+            None
+        };
+        let (stored, compression) = match compressed {
+            Some(ref compressed) => (compressed.as_slice(), LZ4),
+            None => (bytes, STORED),
+        };
+        self.writer.write_all(stored)?;
+
+        let entry = Entry {
+            offset: self.offset,
+            stored_len: stored.len() as u64,
+            size: bytes.len() as u64,
+            compression,
+        };
+        self.offset += entry.stored_len;
+        self.index.retain(|(p, _)| p != path);
+        self.index.push((path.to_owned(), entry));
+        Ok(())
+    }
+
+    /// Writes the index of the pack and returns the underlying writer.
+    pub fn finish(mut self) -> Result<W, Error> {
+        for (path, entry) in &self.index {
+            self.writer.write_all(&(path.len() as u32).to_le_bytes())?;
+            self.writer.write_all(path.as_bytes())?;
+            self.writer.write_all(&entry.offset.to_le_bytes())?;
+            self.writer.write_all(&entry.stored_len.to_le_bytes())?;
+            self.writer.write_all(&entry.size.to_le_bytes())?;
+            self.writer.write_all(&[entry.compression])?;
+        }
+        self.writer.write_all(&self.offset.to_le_bytes())?;
+        self.writer
+            .write_all(&(self.index.len() as u32).to_le_bytes())?;
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+fn read_index(path: &Path) -> Result<HashMap<String, Entry>, Error> {
+    let mut file = File::open(path)?;
+    let file_len = file.metadata()?.len();
+    if file_len < HEADER_LEN + FOOTER_LEN as u64 {
+        return Err(format_err!("Pack is too short"));
+    }
+
+    let mut header = [0; HEADER_LEN as usize];
+    file.read_exact(&mut header)?;
+    if &header[..4] != MAGIC {
+        return Err(format_err!("Not a pack file"));
+    }
+    let version = read_u32(&header[4..]);
+    if version != VERSION {
+        return Err(format_err!("Unsupported pack version {}", version));
+    }
+
+    let mut footer = [0; FOOTER_LEN as usize];
+    file.seek(SeekFrom::End(-FOOTER_LEN))?;
+    file.read_exact(&mut footer)?;
+    let index_offset = read_u64(&footer[..8]);
+    let count = read_u32(&footer[8..]);
+
+    // Every length read from the file is checked against the file before allocating, so a
+    // corrupt pack results in an error instead of a huge allocation.
+    let index_end = file_len - FOOTER_LEN as u64;
+    if index_offset < HEADER_LEN || index_offset > index_end {
+        return Err(format_err!("Pack index lies outside of the pack"));
+    }
+    let mut remaining = index_end - index_offset;
+    if u64::from(count) * MIN_INDEX_ENTRY_LEN > remaining {
+        return Err(format_err!("Pack index is too short for {} entries", count));
+    }
+
+    file.seek(SeekFrom::Start(index_offset))?;
+    let mut entries = HashMap::with_capacity(count as usize);
+    for _ in 0..count {
+        let mut len = [0; 4];
+        file.read_exact(&mut len)?;
+        let path_len = u64::from(read_u32(&len));
+        if path_len + MIN_INDEX_ENTRY_LEN > remaining {
+            return Err(format_err!("Pack index entry exceeds the index"));
+        }
+        remaining -= path_len + MIN_INDEX_ENTRY_LEN;
+        let mut path = vec![0; path_len as usize];
+        file.read_exact(&mut path)?;
+        let path = String::from_utf8(path)?;
+
+        let mut entry = [0; 25];
+        file.read_exact(&mut entry)?;
+        let entry = Entry {
+            offset: read_u64(&entry[..8]),
+            stored_len: read_u64(&entry[8..16]),
+            size: read_u64(&entry[16..24]),
+            compression: entry[24],
+        };
+        let in_bounds = entry.offset >= HEADER_LEN
+            && entry
+                .offset
+                .checked_add(entry.stored_len)
+                .map_or(false, |end| end <= index_offset);
+        if !in_bounds {
+            return Err(format_err!(
+                "Entry {:?} lies outside of the pack data",
+                path
+            ));
+        }
+        // LZ4 can't compress better than 255:1, so a larger size means a corrupt entry.
+        let consistent = match entry.compression {
+            STORED => entry.size == entry.stored_len,
+            _ => entry.size <= entry.stored_len.saturating_mul(255),
+        };
+        if !consistent {
+            return Err(format_err!("Entry {:?} has an inconsistent size", path));
+        }
+        entries.insert(path, entry);
+    }
+
+    Ok(entries)
+}
+
+fn read_entry(path: &Path, entry: &Entry) -> Result<Vec<u8>, Error> {
+    let mut file = File::open(path)?;
+    // The index was checked when the pack was opened, but the file might have changed since.
+    let end = entry
+        .offset
+        .checked_add(entry.stored_len)
+        .ok_or_else(|| format_err!("Entry lies outside of the pack"))?;
+    if end > file.metadata()?.len() {
+        return Err(format_err!("Entry lies outside of the pack"));
+    }
+    file.seek(SeekFrom::Start(entry.offset))?;
+    let mut stored = vec![0; entry.stored_len as usize];
+    file.read_exact(&mut stored)?;
+
+    let bytes = match entry.compression {
+        STORED => stored,
+        LZ4 => lz4_flex::block::decompress(&stored, entry.size as usize)
+            .map_err(|e| format_err!("Failed to decompress entry: {}", e))?,
+        other => return Err(format_err!("Unknown compression {}", other)),
+    };
+    if bytes.len() as u64 != entry.size {
+        return Err(format_err!(
+            "Entry has {} bytes instead of {}",
+            bytes.len(),
+            entry.size
+        ));
+    }
+
+    Ok(bytes)
+}
+
+fn read_u32(bytes: &[u8]) -> u32 {
+    let mut b = [0; 4];
+    b.copy_from_slice(&bytes[..4]);
+    u32::from_le_bytes(b)
+}
+
+fn read_u64(bytes: &[u8]) -> u64 {
+    let mut b = [0; 8];
+    b.copy_from_slice(&bytes[..8]);
+    u64::from_le_bytes(b)
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs::File;
+
+    use crate::source::Source;
+
+    use super::{PackSource, PackWriter};
+
+    #[test]
+    fn loads_assets_from_pack() {
+        let path = std::env::temp_dir().join("amethyst_assets_loads_assets_from_pack.pack");
+        let large = "data".repeat(1000);
+
+        let mut writer = PackWriter::new(File::create(&path).unwrap()).unwrap();
+        writer.add("subdir/asset", b"data", true).unwrap();
+        writer.add("large", large.as_bytes(), true).unwrap();
+        writer.finish().unwrap();
+
+        let pack = PackSource::open(&path).expect("Failed to open pack");
+        assert_eq!(pack.load("subdir/asset").unwrap(), b"data".to_vec());
+        assert_eq!(pack.load("large").unwrap(), large.into_bytes());
+        assert_eq!(pack.load_with_metadata("subdir/asset").unwrap().1, 0);
+        assert!(pack.load("missing").is_err());
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn rejects_corrupt_index() {
+        let path = std::env::temp_dir().join("amethyst_assets_rejects_corrupt_index.pack");
+        let mut writer = PackWriter::new(Vec::new()).unwrap();
+        writer.add("asset", b"data", false).unwrap();
+        let pack = writer.finish().unwrap();
+
+        let index_offset = pack.len() - 12 - (4 + 5 + 25);
+        let corrupt = |offset: usize, bytes: &[u8]| {
+            let mut corrupt = pack.clone();
+            corrupt[offset..offset + bytes.len()].copy_from_slice(bytes);
+            std::fs::write(&path, corrupt).unwrap();
+            PackSource::open(&path)
+        };
+
+        // Entry count
+        assert!(corrupt(pack.len() - 4, &u32::max_value().to_le_bytes()).is_err());
+        // Index offset
+        assert!(corrupt(pack.len() - 12, &u64::max_value().to_le_bytes()).is_err());
+        // Path length
+        assert!(corrupt(index_offset, &u32::max_value().to_le_bytes()).is_err());
+        // Stored length
+        assert!(corrupt(index_offset + 4 + 5 + 8, &u64::max_value().to_le_bytes()).is_err());
+        // Truncated pack
+        std::fs::write(&path, &pack[..pack.len() - 20]).unwrap();
+        assert!(PackSource::open(&path).is_err());
+
+        assert!(corrupt(0, b"AMPK").is_ok());
+        std::fs::remove_file(path).unwrap();
+    }
+}
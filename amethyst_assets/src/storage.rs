@@ -13,6 +13,7 @@ use log::{debug, error, trace, warn};
 use rayon::ThreadPool;
 
 use amethyst_core::{
+    shrev::{EventChannel, EventIterator, ReaderId},
     specs::{
         prelude::{Component, Read, ReadExpect, System, VecStorage, Write},
         storage::UnprotectedStorage,
@@ -52,6 +53,22 @@ pub struct AssetStorage<A: Asset> {
     reloads: Vec<(WeakHandle<A>, Box<dyn Reload<A>>)>,
     unused_handles: MsQueue<Handle<A>>,
     requeue: Mutex<Vec<Processed<A>>>,
+    events: EventChannel<AssetStorageEvent>,
+    fallback: Option<Handle<A>>,
+}
+
+/// A change to the assets of an `AssetStorage`.
+///
+/// Assets are identified by the id of their handle, see `Handle::id`. Note that ids of removed
+/// assets are reused for new ones.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AssetStorageEvent {
+    /// An asset was loaded or cloned into the storage.
+    Inserted(u32),
+    /// An asset was replaced, either by hot reloading or `AssetStorage::replace_data`.
+    Updated(u32),
+    /// An asset was dropped because no handle referenced it anymore.
+    Removed(u32),
 }
 
 /// Returned by processor systems, describes the loading state of the asset.
@@ -105,6 +122,7 @@ impl<A: Asset> AssetStorage<A> {
             unsafe {
                 self.assets.insert(id, asset);
             }
+            self.events.single_write(AssetStorageEvent::Inserted(id));
 
             Some(h)
         } else {
@@ -130,6 +148,58 @@ impl<A: Asset> AssetStorage<A> {
         }
     }
 
+    /// Sets the asset returned by `get_or_fallback` in place of assets that aren't available,
+    /// like a magenta texture for missing textures. The storage keeps the handle alive, so the
+    /// fallback asset isn't dropped.
+    pub fn set_fallback(&mut self, handle: Handle<A>) {
+        self.fallback = Some(handle);
+    }
+
+    /// Returns the handle of the fallback asset, if one was set.
+    pub fn fallback(&self) -> Option<&Handle<A>> {
+        self.fallback.as_ref()
+    }
+
+    /// Get an asset from a given asset handle, or the fallback asset if it is still loading or
+    /// failed to load.
+    ///
+    /// Returns `None` only if neither the asset nor the fallback asset are available.
+    pub fn get_or_fallback(&self, handle: &Handle<A>) -> Option<&A> {
+        self.get(handle).or_else(|| {
+            self.fallback
+                .as_ref()
+                .and_then(|fallback| self.get(fallback))
+        })
+    }
+
+    /// Registers a reader for the `AssetStorageEvent`s of this storage.
+    pub fn register_reader(&mut self) -> ReaderId<AssetStorageEvent> {
+        self.events.register_reader()
+    }
+
+    /// Reads the `AssetStorageEvent`s written since the last read with this reader.
+    pub fn read_events(
+        &self,
+        reader: &mut ReaderId<AssetStorageEvent>,
+    ) -> EventIterator<'_, AssetStorageEvent> {
+        self.events.read(reader)
+    }
+
+    /// Replaces the asset behind `handle` with one created from `data`.
+    ///
+    /// The data is passed through the same processing as newly loaded data, so it is turned into
+    /// an asset the next time this storage gets processed. If processing fails, the old asset is
+    /// kept. The asset behind `handle` has to be loaded already, otherwise the data is discarded.
+    ///
+    /// Note that if the asset was loaded from a file with hot reloading enabled, changes to that
+    /// file will overwrite the replaced asset.
+    pub fn replace_data(&self, handle: &Handle<A>, data: A::Data) {
+        self.processed.push(Processed::Replace {
+            data,
+            handle: handle.clone(),
+        });
+    }
+
     /// Process finished asset data and maintain the storage.
     pub fn process<F>(
         &mut self,
@@ -166,6 +236,7 @@ impl<A: Asset> AssetStorage<A> {
                 let bitset = &mut self.bitset;
                 let handles = &mut self.handles;
                 let reloads = &mut self.reloads;
+                let events = &mut self.events;
 
                 let f = &mut f;
                 let (reload_obj, handle) = match processed {
@@ -245,6 +316,7 @@ impl<A: Asset> AssetStorage<A> {
                         unsafe {
                             assets.insert(id, asset);
                         }
+                        events.single_write(AssetStorageEvent::Inserted(id));
 
                         (reload_obj, handle)
                     }
@@ -301,9 +373,44 @@ impl<A: Asset> AssetStorage<A> {
                             let old = assets.get_mut(id);
                             *old = asset;
                         }
+                        events.single_write(AssetStorageEvent::Updated(id));
 
                         (reload_obj, handle)
                     }
+                    Processed::Replace { data, handle } => {
+                        let id = handle.id();
+                        if !bitset.contains(id) {
+                            warn!(
+                                "{:?}: Cannot replace asset with handle id {:?}, it is not loaded",
+                                A::name(),
+                                handle,
+                            );
+                            continue;
+                        }
+
+                        match f(data) {
+                            Ok(ProcessingState::Loaded(asset)) => {
+                                unsafe {
+                                    let old = assets.get_mut(id);
+                                    *old = asset;
+                                }
+                                events.single_write(AssetStorageEvent::Updated(id));
+                            }
+                            Ok(ProcessingState::Loading(data)) => {
+                                requeue.push(Processed::Replace { data, handle });
+                            }
+                            Err(e) => {
+                                error!(
+                                    "{:?}: Failed to replace asset (handle id: {:?}): {}",
+                                    A::name(),
+                                    handle,
+                                    e,
+                                );
+                            }
+                        }
+
+                        continue;
+                    }
                 };
 
                 // Add the reload obj if it is `Some`.
@@ -330,6 +437,7 @@ impl<A: Asset> AssetStorage<A> {
                 drop_fn(self.assets.remove(id));
             }
             self.bitset.remove(id);
+            self.events.single_write(AssetStorageEvent::Removed(id));
 
             // Can't reuse old handle here, because otherwise weak handles would still be valid.
             // TODO: maybe just store u32?
@@ -402,6 +510,8 @@ impl<A: Asset> Default for AssetStorage<A> {
             reloads: Default::default(),
             unused_handles: MsQueue::new(),
             requeue: Mutex::new(Vec::default()),
+            events: EventChannel::new(),
+            fallback: None,
         }
     }
 }
@@ -516,6 +626,10 @@ pub(crate) enum Processed<A: Asset> {
         name: String,
         old_reload: Box<dyn Reload<A>>,
     },
+    Replace {
+        data: A::Data,
+        handle: Handle<A>,
+    },
 }
 
 /// A weak handle, which is useful if you don't directly need the asset
@@ -543,3 +657,195 @@ impl<A> WeakHandle<A> {
         self.upgrade().is_none()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use rayon::{ThreadPool, ThreadPoolBuilder};
+
+    use amethyst_core::specs::prelude::VecStorage;
+    use amethyst_error::Error;
+
+    use crate::{
+        asset::{Asset, FormatValue},
+        reload::Reload,
+    };
+
+    use super::{AssetStorage, AssetStorageEvent, Handle, Processed, ProcessingState};
+
+    #[derive(Debug, PartialEq)]
+    struct TestAsset(u32);
+
+    impl Asset for TestAsset {
+        fn name() -> &'static str {
+            "TEST"
+        }
+        type Data = u32;
+        type HandleStorage = VecStorage<Handle<Self>>;
+    }
+
+    #[derive(Clone)]
+    struct TestReload;
+
+    impl Reload<TestAsset> for TestReload {
+        fn needs_reload(&self) -> bool {
+            false
+        }
+
+        fn name(&self) -> String {
+            "test".to_owned()
+        }
+
+        fn format(&self) -> &'static str {
+            "TEST"
+        }
+
+        fn reload(self: Box<Self>) -> Result<FormatValue<TestAsset>, Error> {
+            Err(Error::from_string("Not reloadable"))
+        }
+    }
+
+    fn pool() -> ThreadPool {
+        ThreadPoolBuilder::new().num_threads(1).build().unwrap()
+    }
+
+    fn load(storage: &AssetStorage<TestAsset>, data: u32) -> Handle<TestAsset> {
+        let handle = storage.allocate();
+        storage.processed.push(Processed::NewAsset {
+            data: Ok(FormatValue::data(data)),
+            handle: handle.clone(),
+            name: "test".to_owned(),
+            tracker: Box::new(()),
+        });
+        handle
+    }
+
+    // Processes the storage, failing to turn 0 into an asset.
+    fn process(storage: &mut AssetStorage<TestAsset>, pool: &ThreadPool) {
+        storage.process(
+            |data| {
+                if data == 0 {
+                    Err(Error::from_string("Zero is not an asset"))
+                } else {
+                    Ok(ProcessingState::Loaded(TestAsset(data)))
+                }
+            },
+            0,
+            pool,
+            None,
+        );
+    }
+
+    #[test]
+    fn events_follow_asset_lifecycle() {
+        let pool = pool();
+        let mut storage = AssetStorage::<TestAsset>::new();
+        let mut reader = storage.register_reader();
+
+        let handle = load(&storage, 1);
+        let id = handle.id();
+        process(&mut storage, &pool);
+        let events: Vec<_> = storage.read_events(&mut reader).cloned().collect();
+        assert_eq!(events, vec![AssetStorageEvent::Inserted(id)]);
+
+        storage.processed.push(Processed::HotReload {
+            data: Ok(FormatValue::data(2)),
+            handle: handle.clone(),
+            name: "test".to_owned(),
+            old_reload: Box::new(TestReload),
+        });
+        process(&mut storage, &pool);
+        let events: Vec<_> = storage.read_events(&mut reader).cloned().collect();
+        assert_eq!(events, vec![AssetStorageEvent::Updated(id)]);
+
+        storage.replace_data(&handle, 3);
+        process(&mut storage, &pool);
+        let events: Vec<_> = storage.read_events(&mut reader).cloned().collect();
+        assert_eq!(events, vec![AssetStorageEvent::Updated(id)]);
+
+        // The asset stays as long as any handle to it is alive.
+        let clone = handle.clone();
+        drop(handle);
+        process(&mut storage, &pool);
+        assert_eq!(storage.read_events(&mut reader).count(), 0);
+
+        drop(clone);
+        process(&mut storage, &pool);
+        let events: Vec<_> = storage.read_events(&mut reader).cloned().collect();
+        assert_eq!(events, vec![AssetStorageEvent::Removed(id)]);
+    }
+
+    #[test]
+    fn replace_data_replaces_loaded_asset() {
+        let pool = pool();
+        let mut storage = AssetStorage::<TestAsset>::new();
+        let handle = load(&storage, 1);
+        process(&mut storage, &pool);
+
+        storage.replace_data(&handle, 2);
+        assert_eq!(storage.get(&handle), Some(&TestAsset(1)));
+        process(&mut storage, &pool);
+        assert_eq!(storage.get(&handle), Some(&TestAsset(2)));
+    }
+
+    #[test]
+    fn replace_data_discards_data_for_unloaded_asset() {
+        let pool = pool();
+        let mut storage = AssetStorage::<TestAsset>::new();
+        let mut reader = storage.register_reader();
+        let handle = storage.allocate();
+
+        storage.replace_data(&handle, 2);
+        process(&mut storage, &pool);
+        assert!(storage.get(&handle).is_none());
+        assert_eq!(storage.read_events(&mut reader).count(), 0);
+
+        // The data is not applied later either.
+        storage.processed.push(Processed::NewAsset {
+            data: Ok(FormatValue::data(1)),
+            handle: handle.clone(),
+            name: "test".to_owned(),
+            tracker: Box::new(()),
+        });
+        process(&mut storage, &pool);
+        process(&mut storage, &pool);
+        assert_eq!(storage.get(&handle), Some(&TestAsset(1)));
+    }
+
+    #[test]
+    fn replace_data_keeps_old_asset_on_error() {
+        let pool = pool();
+        let mut storage = AssetStorage::<TestAsset>::new();
+        let handle = load(&storage, 1);
+        process(&mut storage, &pool);
+        let mut reader = storage.register_reader();
+
+        storage.replace_data(&handle, 0);
+        process(&mut storage, &pool);
+        assert_eq!(storage.get(&handle), Some(&TestAsset(1)));
+        assert_eq!(storage.read_events(&mut reader).count(), 0);
+    }
+
+    #[test]
+    fn get_or_fallback_returns_fallback_for_unavailable_assets() {
+        let pool = pool();
+        let mut storage = AssetStorage::<TestAsset>::new();
+        let loaded = load(&storage, 1);
+        let failed = load(&storage, 0);
+        process(&mut storage, &pool);
+        let loading = load(&storage, 3);
+
+        // Without a fallback, only loaded assets are returned.
+        assert_eq!(storage.get_or_fallback(&loaded), Some(&TestAsset(1)));
+        assert!(storage.get_or_fallback(&failed).is_none());
+        assert!(storage.get_or_fallback(&loading).is_none());
+
+        let fallback = load(&storage, 2);
+        process(&mut storage, &pool);
+        storage.set_fallback(fallback);
+        let loading = load(&storage, 3);
+
+        assert_eq!(storage.get_or_fallback(&loaded), Some(&TestAsset(1)));
+        assert_eq!(storage.get_or_fallback(&failed), Some(&TestAsset(2)));
+        assert_eq!(storage.get_or_fallback(&loading), Some(&TestAsset(2)));
+    }
+}
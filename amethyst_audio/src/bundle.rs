@@ -4,7 +4,11 @@ use amethyst_assets::Processor;
 use amethyst_core::{bundle::SystemBundle, specs::prelude::DispatcherBuilder};
 use amethyst_error::Error;
 
-use crate::{output::Output, source::*, systems::AudioSystem};
+use crate::{
+    output::Output,
+    source::*,
+    systems::{AudioSystem, OneShotSoundSystem},
+};
 
 /// Audio bundle
 ///
@@ -12,12 +16,41 @@ use crate::{output::Output, source::*, systems::AudioSystem};
 ///
 /// `DjSystem` must be added separately if you want to use our background music system.
 ///
+/// The `OneShotSoundSystem` playing the sounds requested through `OneShotSounds` is added with
+/// `with_one_shot_sounds`.
+///
 #[derive(Default)]
-pub struct AudioBundle(Output);
+pub struct AudioBundle {
+    output: Output,
+    one_shot_emitters: Option<usize>,
+}
+
+impl AudioBundle {
+    /// Adds the `OneShotSoundSystem`, using at most `max_emitters` emitter entities, so sounds
+    /// can be played through the `OneShotSounds` resource.
+    pub fn with_one_shot_sounds(mut self, max_emitters: usize) -> Self {
+        self.one_shot_emitters = Some(max_emitters);
+        self
+    }
+}
 
 impl<'a, 'b> SystemBundle<'a, 'b> for AudioBundle {
     fn build(self, builder: &mut DispatcherBuilder<'a, 'b>) -> Result<(), Error> {
-        builder.add(AudioSystem::new(self.0), "audio_system", &[]);
+        match self.one_shot_emitters {
+            Some(max_emitters) => {
+                builder.add(
+                    OneShotSoundSystem::new(max_emitters),
+                    "one_shot_sound_system",
+                    &[],
+                );
+                builder.add(
+                    AudioSystem::new(self.output),
+                    "audio_system",
+                    &["one_shot_sound_system"],
+                );
+            }
+            None => builder.add(AudioSystem::new(self.output), "audio_system", &[]),
+        }
         builder.add(Processor::<Source>::new(), "source_processor", &[]);
         Ok(())
     }
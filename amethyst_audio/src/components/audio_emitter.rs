@@ -3,18 +3,24 @@ use std::{
     sync::{atomic::AtomicBool, Arc},
 };
 
-use rodio::{Decoder, SpatialSink};
+use rodio::{
+    source::{Amplify, Speed},
+    Decoder, Source as RodioSource, SpatialSink,
+};
 use smallvec::SmallVec;
 
 use amethyst_core::specs::{prelude::Component, storage::BTreeStorage};
 
 use crate::{source::Source, DecoderError};
 
+/// A decoded sound together with its volume and pitch.
+pub(crate) type QueuedSound = Speed<Amplify<Decoder<Cursor<Source>>>>;
+
 /// An audio source, add this component to anything that emits sound.
 #[derive(Default)]
 pub struct AudioEmitter {
     pub(crate) sinks: SmallVec<[(SpatialSink, Arc<AtomicBool>); 4]>,
-    pub(crate) sound_queue: SmallVec<[Decoder<Cursor<Source>>; 4]>,
+    pub(crate) sound_queue: SmallVec<[QueuedSound; 4]>,
     pub(crate) picker: Option<Box<dyn FnMut(&mut AudioEmitter) -> bool + Send + Sync>>,
 }
 
@@ -28,8 +34,19 @@ impl AudioEmitter {
 
     /// Plays an audio source from this emitter.
     pub fn play(&mut self, source: &Source) -> Result<(), DecoderError> {
-        self.sound_queue
-            .push(Decoder::new(Cursor::new(source.clone())).map_err(|_| DecoderError)?);
+        self.play_with(source, 1.0, 1.0)
+    }
+
+    /// Plays an audio source from this emitter, scaling its amplitude by `volume` and its
+    /// playback speed by `pitch`.
+    pub(crate) fn play_with(
+        &mut self,
+        source: &Source,
+        volume: f32,
+        pitch: f32,
+    ) -> Result<(), DecoderError> {
+        let decoder = Decoder::new(Cursor::new(source.clone())).map_err(|_| DecoderError)?;
+        self.sound_queue.push(decoder.amplify(volume).speed(pitch));
         Ok(())
     }
 
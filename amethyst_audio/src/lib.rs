@@ -5,7 +5,7 @@ pub use self::{
     bundle::AudioBundle,
     components::*,
     formats::{AudioFormat, FlacFormat, Mp3Format, OggFormat, WavFormat},
-    sink::AudioSink,
+    sink::{AudioSink, Easing},
     source::{Source, SourceHandle},
     systems::*,
 };
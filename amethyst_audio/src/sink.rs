@@ -1,12 +1,22 @@
-use std::io::Cursor;
+use std::{
+    io::Cursor,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
 
-use rodio::{Decoder, Sink};
+use rodio::{Decoder, Sink, Source as RodioSource};
+
+use amethyst_core::timing::duration_to_secs;
 
 use crate::{output::Output, source::Source, DecoderError};
 
+// How often the audio thread picks up the current fade volume.
+const FADE_UPDATE_INTERVAL: Duration = Duration::from_millis(5);
+
 /// This structure provides a way to programmatically pick and play music.
 pub struct AudioSink {
     sink: Sink,
+    fade: Arc<Mutex<Fade>>,
 }
 
 impl AudioSink {
@@ -14,13 +24,24 @@ impl AudioSink {
     pub fn new(output: &Output) -> AudioSink {
         AudioSink {
             sink: Sink::new(&output.device),
+            fade: Arc::new(Mutex::new(Fade::default())),
         }
     }
 
     /// Adds a source to the sink's queue of music to play.
     pub fn append(&self, source: &Source) -> Result<(), DecoderError> {
-        self.sink
-            .append(Decoder::new(Cursor::new(source.clone())).map_err(|_| DecoderError)?);
+        let fade = self.fade.clone();
+        let decoder = Decoder::new(Cursor::new(source.clone())).map_err(|_| DecoderError)?;
+        self.sink.append(decoder.amplify(1.0).periodic_access(
+            FADE_UPDATE_INTERVAL,
+            move |source| {
+                let volume = fade
+                    .lock()
+                    .expect("The mutex of the fade in `AudioSink` was poisoned")
+                    .volume(Instant::now());
+                source.set_factor(volume);
+            },
+        ));
         Ok(())
     }
 
@@ -39,6 +60,34 @@ impl AudioSink {
         self.sink.set_volume(volume);
     }
 
+    /// Smoothly changes the fade volume to `volume` over `duration`.
+    ///
+    /// The fade volume starts at 1.0 and is applied on top of the volume set with `set_volume`,
+    /// so fading to 0.0 and back to 1.0 restores the original volume. The fade is applied on the
+    /// audio thread and continues from the current fade volume if another fade is in progress.
+    pub fn fade_to(&self, volume: f32, duration: Duration, easing: Easing) {
+        let mut fade = self
+            .fade
+            .lock()
+            .expect("The mutex of the fade in `AudioSink` was poisoned");
+        let now = Instant::now();
+        *fade = Fade {
+            from: fade.volume(now),
+            to: volume,
+            start: now,
+            duration,
+            easing,
+        };
+    }
+
+    /// Retrieves the current fade volume, see `fade_to`.
+    pub fn fade_volume(&self) -> f32 {
+        self.fade
+            .lock()
+            .expect("The mutex of the fade in `AudioSink` was poisoned")
+            .volume(Instant::now())
+    }
+
     /// Resumes playback of a paused sink. Has no effect if this sink was never paused.
     pub fn play(&self) {
         self.sink.play();
@@ -59,3 +108,87 @@ impl AudioSink {
         self.sink.stop();
     }
 }
+
+/// The curve a fade follows, see `AudioSink::fade_to`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Easing {
+    /// Changes the volume at a constant rate.
+    Linear,
+    /// Starts slowly and speeds up towards the end.
+    EaseIn,
+    /// Starts quickly and slows down towards the end.
+    EaseOut,
+    /// Starts and ends slowly.
+    EaseInOut,
+}
+
+impl Easing {
+    /// Maps the progress `t` of a fade, between 0.0 and 1.0, onto this curve.
+    pub fn apply(self, t: f32) -> f32 {
+        match self {
+            Easing::Linear => t,
+            Easing::EaseIn => t * t,
+            Easing::EaseOut => t * (2.0 - t),
+            Easing::EaseInOut => t * t * (3.0 - 2.0 * t),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Fade {
+    from: f32,
+    to: f32,
+    start: Instant,
+    duration: Duration,
+    easing: Easing,
+}
+
+impl Fade {
+    fn volume(&self, now: Instant) -> f32 {
+        let duration = duration_to_secs(self.duration);
+        if duration <= 0.0 {
+            return self.to;
+        }
+        if now <= self.start {
+            return self.from;
+        }
+        let elapsed = now.duration_since(self.start);
+        let t = (duration_to_secs(elapsed) / duration).min(1.0);
+        self.from + (self.to - self.from) * self.easing.apply(t)
+    }
+}
+
+impl Default for Fade {
+    fn default() -> Self {
+        Fade {
+            from: 1.0,
+            to: 1.0,
+            start: Instant::now(),
+            duration: Duration::from_secs(0),
+            easing: Easing::Linear,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::{Easing, Fade};
+
+    #[test]
+    fn fade_follows_easing() {
+        let start = Instant::now();
+        let fade = Fade {
+            from: 1.0,
+            to: 0.0,
+            start,
+            duration: Duration::from_secs(2),
+            easing: Easing::EaseIn,
+        };
+
+        assert_eq!(fade.volume(start), 1.0);
+        assert_eq!(fade.volume(start + Duration::from_secs(1)), 0.75);
+        assert_eq!(fade.volume(start + Duration::from_secs(3)), 0.0);
+    }
+}
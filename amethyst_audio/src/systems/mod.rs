@@ -1,6 +1,11 @@
 //! `amethyst` audio ecs systems
 
-pub use self::{audio::AudioSystem, dj::DjSystem};
+pub use self::{
+    audio::AudioSystem,
+    dj::DjSystem,
+    one_shot::{OneShotParams, OneShotSoundSystem, OneShotSounds},
+};
 
 mod audio;
 mod dj;
+mod one_shot;
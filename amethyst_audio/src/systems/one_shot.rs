@@ -0,0 +1,349 @@
+use std::sync::atomic::Ordering;
+
+use log::warn;
+
+#[cfg(feature = "profiler")]
+use thread_profiler::profile_scope;
+
+use amethyst_assets::AssetStorage;
+use amethyst_core::{
+    nalgebra::{Matrix4, Point3},
+    specs::prelude::{
+        Entities, Entity, Join, Read, ReadStorage, Resources, System, SystemData, Write,
+        WriteStorage,
+    },
+    transform::{GlobalTransform, Transform},
+};
+
+use crate::{
+    components::{AudioEmitter, AudioListener},
+    source::{Source, SourceHandle},
+};
+
+/// How a sound requested through `OneShotSounds` is played.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct OneShotParams {
+    /// Factor the amplitude of the sound is multiplied with.
+    pub volume: f32,
+    /// Factor the playback speed of the sound is multiplied with, which also changes its pitch.
+    pub pitch: f32,
+}
+
+impl Default for OneShotParams {
+    fn default() -> Self {
+        OneShotParams {
+            volume: 1.0,
+            pitch: 1.0,
+        }
+    }
+}
+
+/// Resource for playing sounds at a position without managing emitter entities.
+///
+/// The sounds are played by the `OneShotSoundSystem`, which is added with
+/// `AudioBundle::with_one_shot_sounds`.
+#[derive(Default)]
+pub struct OneShotSounds {
+    queue: Vec<(Point3<f32>, SourceHandle, OneShotParams)>,
+}
+
+impl OneShotSounds {
+    /// Plays an audio source once at the given position.
+    ///
+    /// The source has to be loaded and an `AudioListener` has to exist by the time the
+    /// `OneShotSoundSystem` runs, otherwise the sound is dropped.
+    pub fn play_at(&mut self, position: Point3<f32>, source: SourceHandle, params: OneShotParams) {
+        self.queue.push((position, source, params));
+    }
+}
+
+/// Plays the sounds requested through `OneShotSounds`.
+///
+/// Every sound is played by an emitter entity from a pool. Entities are reused once their sound
+/// has finished playing, so the pool only grows to the number of sounds playing at the same time,
+/// up to `max_emitters`. Sounds requested while all emitters are busy or while there is no
+/// `AudioListener` are dropped.
+///
+/// Emitters are positioned through their `Transform`. Their `GlobalTransform` is set as well, so
+/// the sound starts at the right position even if the `TransformSystem` has not run yet.
+///
+/// Has to run before the `AudioSystem` for sounds to start in the same frame.
+pub struct OneShotSoundSystem {
+    pool: Vec<Entity>,
+    max_emitters: usize,
+}
+
+impl OneShotSoundSystem {
+    /// Creates a new system using at most `max_emitters` emitter entities.
+    pub fn new(max_emitters: usize) -> Self {
+        OneShotSoundSystem {
+            pool: Vec::new(),
+            max_emitters,
+        }
+    }
+}
+
+impl Default for OneShotSoundSystem {
+    fn default() -> Self {
+        OneShotSoundSystem::new(32)
+    }
+}
+
+impl<'a> System<'a> for OneShotSoundSystem {
+    type SystemData = (
+        Entities<'a>,
+        Read<'a, AssetStorage<Source>>,
+        Write<'a, OneShotSounds>,
+        ReadStorage<'a, AudioListener>,
+        WriteStorage<'a, AudioEmitter>,
+        WriteStorage<'a, Transform>,
+        WriteStorage<'a, GlobalTransform>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            entities,
+            storage,
+            mut sounds,
+            listeners,
+            mut emitters,
+            mut locals,
+            mut globals,
+        ): Self::SystemData,
+    ) {
+        #[cfg(feature = "profiler")]
+        profile_scope!("one_shot_sound_system");
+
+        self.pool.retain(|&entity| entities.is_alive(entity));
+        // The `AudioSystem` only removes finished sinks while there is a listener, so this is
+        // done here as well to free emitters in any case.
+        for &entity in &self.pool {
+            if let Some(emitter) = emitters.get_mut(entity) {
+                emitter
+                    .sinks
+                    .retain(|(_, finished)| !finished.load(Ordering::Relaxed));
+            }
+        }
+
+        if listeners.join().next().is_none() {
+            if !sounds.queue.is_empty() {
+                warn!("There is no audio listener, dropping one-shot sounds");
+                sounds.queue.clear();
+            }
+            return;
+        }
+
+        for (position, handle, params) in sounds.queue.drain(..) {
+            let source = match storage.get(&handle) {
+                Some(source) => source,
+                None => {
+                    warn!("One-shot sound is not loaded, dropping it");
+                    continue;
+                }
+            };
+            let idle = self.pool.iter().cloned().find(|&entity| {
+                emitters.get(entity).map_or(true, |emitter| {
+                    emitter.sinks.is_empty() && emitter.sound_queue.is_empty()
+                })
+            });
+            let entity = match idle {
+                Some(entity) => entity,
+                None if self.pool.len() < self.max_emitters => {
+                    let entity = entities.create();
+                    self.pool.push(entity);
+                    entity
+                }
+                None => {
+                    warn!("All one-shot sound emitters are busy, dropping sound");
+                    continue;
+                }
+            };
+
+            let mut local = Transform::default();
+            local.set_position(position.coords);
+            locals
+                .insert(entity, local)
+                .expect("Unreachable: Pooled entities are alive");
+            globals
+                .insert(
+                    entity,
+                    GlobalTransform(Matrix4::new_translation(&position.coords)),
+                )
+                .expect("Unreachable: Pooled entities are alive");
+            if !emitters.contains(entity) {
+                emitters
+                    .insert(entity, AudioEmitter::new())
+                    .expect("Unreachable: Pooled entities are alive");
+            }
+            if let Some(emitter) = emitters.get_mut(entity) {
+                if emitter
+                    .play_with(source, params.volume, params.pitch)
+                    .is_err()
+                {
+                    warn!("Failed to decode one-shot sound, dropping it");
+                }
+            }
+        }
+    }
+
+    fn setup(&mut self, res: &mut Resources) {
+        Self::SystemData::setup(res);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use rayon::ThreadPoolBuilder;
+
+    use amethyst_assets::{AssetStorage, Loader};
+    use amethyst_core::{
+        nalgebra::Point3,
+        specs::{Builder, Join, RunNow, World},
+        transform::{GlobalTransform, Transform},
+    };
+
+    use crate::{
+        components::{AudioEmitter, AudioListener},
+        formats::AudioData,
+        source::{Source, SourceHandle},
+    };
+
+    use super::{OneShotParams, OneShotSoundSystem, OneShotSounds};
+
+    // A short silent mono WAV file.
+    fn silence() -> AudioData {
+        let data_len = 32u32;
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(b"RIFF");
+        bytes.extend_from_slice(&(36 + data_len).to_le_bytes());
+        bytes.extend_from_slice(b"WAVEfmt ");
+        bytes.extend_from_slice(&16u32.to_le_bytes());
+        bytes.extend_from_slice(&1u16.to_le_bytes());
+        bytes.extend_from_slice(&1u16.to_le_bytes());
+        bytes.extend_from_slice(&8000u32.to_le_bytes());
+        bytes.extend_from_slice(&16000u32.to_le_bytes());
+        bytes.extend_from_slice(&2u16.to_le_bytes());
+        bytes.extend_from_slice(&16u16.to_le_bytes());
+        bytes.extend_from_slice(b"data");
+        bytes.extend_from_slice(&data_len.to_le_bytes());
+        bytes.resize(bytes.len() + data_len as usize, 0);
+        AudioData(bytes)
+    }
+
+    fn setup(system: &mut OneShotSoundSystem, listener: bool) -> (World, SourceHandle) {
+        let mut world = World::new();
+        let pool = Arc::new(ThreadPoolBuilder::default().build().unwrap());
+        world.add_resource(Loader::new(".", pool.clone()));
+        world.add_resource(AssetStorage::<Source>::new());
+        RunNow::setup(system, &mut world.res);
+
+        let handle = world.read_resource::<Loader>().load_from_data(
+            silence(),
+            (),
+            &world.read_resource::<AssetStorage<Source>>(),
+        );
+        world
+            .write_resource::<AssetStorage<Source>>()
+            .process(Into::into, 0, &pool, None);
+        if listener {
+            world
+                .create_entity()
+                .with(AudioListener::default())
+                .with(GlobalTransform::default())
+                .build();
+        }
+
+        (world, handle)
+    }
+
+    fn play(world: &World, handle: &SourceHandle, count: usize) {
+        let mut sounds = world.write_resource::<OneShotSounds>();
+        for _ in 0..count {
+            sounds.play_at(Point3::origin(), handle.clone(), OneShotParams::default());
+        }
+    }
+
+    fn queued_sounds(world: &World) -> usize {
+        world
+            .read_storage::<AudioEmitter>()
+            .join()
+            .map(|emitter| emitter.sound_queue.len())
+            .sum()
+    }
+
+    #[test]
+    fn finished_emitters_are_reused() {
+        let mut system = OneShotSoundSystem::new(4);
+        let (world, handle) = setup(&mut system, true);
+
+        play(&world, &handle, 1);
+        system.run_now(&world.res);
+        assert_eq!(system.pool.len(), 1);
+        assert_eq!(queued_sounds(&world), 1);
+
+        // Playing a second sound while the first one has not started needs another emitter.
+        play(&world, &handle, 1);
+        system.run_now(&world.res);
+        assert_eq!(system.pool.len(), 2);
+
+        // Once the `AudioSystem` took the sounds and they finished, the emitters are idle.
+        for emitter in (&mut world.write_storage::<AudioEmitter>()).join() {
+            emitter.sound_queue.clear();
+        }
+        play(&world, &handle, 2);
+        system.run_now(&world.res);
+        assert_eq!(system.pool.len(), 2);
+        assert_eq!(queued_sounds(&world), 2);
+    }
+
+    #[test]
+    fn emitters_are_placed_at_the_sound_position() {
+        let mut system = OneShotSoundSystem::default();
+        let (world, handle) = setup(&mut system, true);
+
+        let position = Point3::new(1.0, 2.0, 3.0);
+        world.write_resource::<OneShotSounds>().play_at(
+            position,
+            handle.clone(),
+            OneShotParams::default(),
+        );
+        system.run_now(&world.res);
+
+        let entity = system.pool[0];
+        let locals = world.read_storage::<Transform>();
+        assert_eq!(*locals.get(entity).unwrap().translation(), position.coords);
+        let globals = world.read_storage::<GlobalTransform>();
+        assert_eq!(
+            globals.get(entity).unwrap().0.column(3).xyz(),
+            position.coords
+        );
+    }
+
+    #[test]
+    fn sounds_above_max_emitters_are_dropped() {
+        let mut system = OneShotSoundSystem::new(2);
+        let (world, handle) = setup(&mut system, true);
+
+        play(&world, &handle, 3);
+        system.run_now(&world.res);
+
+        assert_eq!(system.pool.len(), 2);
+        assert_eq!(queued_sounds(&world), 2);
+        assert!(world.read_resource::<OneShotSounds>().queue.is_empty());
+    }
+
+    #[test]
+    fn sounds_without_listener_are_dropped() {
+        let mut system = OneShotSoundSystem::default();
+        let (world, handle) = setup(&mut system, false);
+
+        play(&world, &handle, 1);
+        system.run_now(&world.res);
+
+        assert!(system.pool.is_empty());
+        assert!(world.read_resource::<OneShotSounds>().queue.is_empty());
+    }
+}
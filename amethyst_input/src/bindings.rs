@@ -12,6 +12,10 @@ use fnv::FnvHashMap as HashMap;
 use serde::{Deserialize, Serialize};
 use smallvec::SmallVec;
 
+use amethyst_assets::{Asset, Handle, ProcessingState};
+use amethyst_core::specs::prelude::VecStorage;
+use amethyst_error::Error as AmethystError;
+
 use super::{Axis, Button};
 
 /// Used for saving and loading input settings.
@@ -39,7 +43,9 @@ use super::{Axis, Button};
 ///     }
 /// )
 /// ```
-#[derive(Derivative, Serialize, Deserialize, Clone)]
+///
+/// Bindings can also be loaded as an asset with `RonFormat`, see `BindingsReloadSystem`.
+#[derive(Derivative, Serialize, Deserialize, Clone, PartialEq)]
 #[derivative(Default(bound = ""))]
 pub struct Bindings<AX = String, AC = String>
 where
@@ -402,6 +408,29 @@ where
     }
 }
 
+impl<AX, AC> Asset for Bindings<AX, AC>
+where
+    AX: Hash + Eq + Clone + Debug + Display + Send + Sync + 'static,
+    AC: Hash + Eq + Clone + Debug + Display + Send + Sync + 'static,
+{
+    fn name() -> &'static str {
+        "input::Bindings"
+    }
+    type Data = Self;
+    type HandleStorage = VecStorage<Handle<Self>>;
+}
+
+impl<AX, AC> Into<Result<ProcessingState<Bindings<AX, AC>>, AmethystError>> for Bindings<AX, AC>
+where
+    AX: Hash + Eq + Clone + Debug + Display + Send + Sync + 'static,
+    AC: Hash + Eq + Clone + Debug + Display + Send + Sync + 'static,
+{
+    fn into(mut self) -> Result<ProcessingState<Bindings<AX, AC>>, AmethystError> {
+        self.check_invariants().map_err(AmethystError::new)?;
+        Ok(ProcessingState::Loaded(self))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,156 @@
+//! Hot reloading of input bindings
+
+use std::{
+    fmt::{Debug, Display},
+    hash::Hash,
+};
+
+use amethyst_assets::{AssetStorage, AssetStorageEvent, Handle};
+use amethyst_core::{
+    shrev::ReaderId,
+    specs::prelude::{Read, Resources, System, SystemData, Write},
+};
+
+use crate::{Bindings, InputHandler};
+
+/// Keeps the bindings of the `InputHandler` in sync with a `Bindings` asset.
+///
+/// Whenever the asset behind the handle is inserted or updated, for example because its file was
+/// hot reloaded, its bindings replace the ones of the `InputHandler`. Changes made to the `InputHandler`
+/// bindings in code are kept until the asset changes again.
+///
+/// The asset is loaded like any other, usually with `RonFormat`, and needs a
+/// `Processor<Bindings<AX, AC>>` to be added to the dispatcher.
+pub struct BindingsReloadSystem<AX, AC>
+where
+    AX: Hash + Eq + Clone,
+    AC: Hash + Eq + Clone,
+{
+    handle: Handle<Bindings<AX, AC>>,
+    reader: Option<ReaderId<AssetStorageEvent>>,
+    changed: bool,
+}
+
+impl<AX, AC> BindingsReloadSystem<AX, AC>
+where
+    AX: Hash + Eq + Clone,
+    AC: Hash + Eq + Clone,
+{
+    /// Creates a new system applying the bindings behind `handle`.
+    pub fn new(handle: Handle<Bindings<AX, AC>>) -> Self {
+        BindingsReloadSystem {
+            handle,
+            reader: None,
+            changed: false,
+        }
+    }
+}
+
+impl<'a, AX, AC> System<'a> for BindingsReloadSystem<AX, AC>
+where
+    AX: Hash + Eq + Clone + Debug + Display + Send + Sync + 'static,
+    AC: Hash + Eq + Clone + Debug + Display + Send + Sync + 'static,
+{
+    type SystemData = (
+        Read<'a, AssetStorage<Bindings<AX, AC>>>,
+        Write<'a, InputHandler<AX, AC>>,
+    );
+
+    fn run(&mut self, (storage, mut handler): Self::SystemData) {
+        let id = self.handle.id();
+        let reader = self.reader.as_mut().expect(
+            "`BindingsReloadSystem::setup` was not called before `BindingsReloadSystem::run`",
+        );
+        for event in storage.read_events(reader) {
+            match *event {
+                AssetStorageEvent::Inserted(i) | AssetStorageEvent::Updated(i) if i == id => {
+                    self.changed = true;
+                }
+                _ => {}
+            }
+        }
+
+        if self.changed {
+            if let Some(bindings) = storage.get(&self.handle) {
+                handler.bindings = bindings.clone();
+                self.changed = false;
+            }
+        }
+    }
+
+    fn setup(&mut self, res: &mut Resources) {
+        Self::SystemData::setup(res);
+        let mut storage = res.fetch_mut::<AssetStorage<Bindings<AX, AC>>>();
+        // The asset might have been loaded before the system was set up.
+        self.changed = storage.get(&self.handle).is_some();
+        self.reader = Some(storage.register_reader());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use rayon::ThreadPoolBuilder;
+
+    use amethyst_assets::{AssetStorage, Loader};
+    use amethyst_core::specs::{RunNow, World};
+    use winit::VirtualKeyCode;
+
+    use crate::{Bindings, Button, InputHandler};
+
+    use super::BindingsReloadSystem;
+
+    fn bindings(key: VirtualKeyCode) -> Bindings<String, String> {
+        let mut bindings = Bindings::new();
+        bindings
+            .insert_action_binding("jump".to_owned(), Some(Button::Key(key)))
+            .unwrap();
+        bindings
+    }
+
+    #[test]
+    fn updated_asset_replaces_bindings() {
+        let mut world = World::new();
+        let pool = Arc::new(ThreadPoolBuilder::new().num_threads(1).build().unwrap());
+        world.add_resource(Loader::new(".", pool.clone()));
+        world.add_resource(AssetStorage::<Bindings<String, String>>::new());
+        let handle = world.read_resource::<Loader>().load_from_data(
+            bindings(VirtualKeyCode::Space),
+            (),
+            &world.read_resource::<AssetStorage<Bindings<String, String>>>(),
+        );
+        let mut system = BindingsReloadSystem::new(handle.clone());
+        RunNow::setup(&mut system, &mut world.res);
+
+        let process = |world: &World| {
+            world
+                .write_resource::<AssetStorage<Bindings<String, String>>>()
+                .process(Into::into, 0, &pool, None);
+        };
+        let applied = |world: &World, key| {
+            world
+                .read_resource::<InputHandler<String, String>>()
+                .bindings
+                == bindings(key)
+        };
+
+        process(&world);
+        system.run_now(&world.res);
+        assert!(applied(&world, VirtualKeyCode::Space));
+
+        // Changes made in code are kept until the asset changes.
+        world
+            .write_resource::<InputHandler<String, String>>()
+            .bindings = Bindings::new();
+        system.run_now(&world.res);
+        assert!(!applied(&world, VirtualKeyCode::Space));
+
+        world
+            .read_resource::<AssetStorage<Bindings<String, String>>>()
+            .replace_data(&handle, bindings(VirtualKeyCode::Return));
+        process(&world);
+        system.run_now(&world.res);
+        assert!(applied(&world, VirtualKeyCode::Return));
+    }
+}
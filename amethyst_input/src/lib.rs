@@ -7,6 +7,7 @@ pub use self::sdl_events_system::SdlEventsSystem;
 pub use self::{
     axis::Axis,
     bindings::{BindingError, Bindings},
+    bindings_reload_system::BindingsReloadSystem,
     bundle::{BindingsFileError, InputBundle},
     button::Button,
     controller::{ControllerAxis, ControllerButton, ControllerEvent},
@@ -23,6 +24,7 @@ use winit::{self, VirtualKeyCode};
 
 mod axis;
 mod bindings;
+mod bindings_reload_system;
 mod bundle;
 mod button;
 mod controller;
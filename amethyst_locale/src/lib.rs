@@ -4,9 +4,11 @@
 
 #![warn(missing_docs, rust_2018_idioms, rust_2018_compatibility)]
 
-use fluent::bundle::FluentBundle;
+use std::collections::HashMap;
 
-use amethyst_assets::{Asset, Handle, ProcessingState, SimpleFormat};
+use fluent::{bundle::FluentBundle, types::FluentValue};
+
+use amethyst_assets::{Asset, AssetStorage, Handle, ProcessingState, SimpleFormat};
 use amethyst_core::specs::prelude::VecStorage;
 use amethyst_error::Error;
 
@@ -50,3 +52,149 @@ impl Asset for Locale {
     type Data = Locale;
     type HandleStorage = VecStorage<LocaleHandle>;
 }
+
+/// Resource keeping track of the available locales and the one currently in use.
+///
+/// Messages are looked up in the asset storage on every call, so translations reloaded through
+/// the `Loader` are picked up immediately.
+#[derive(Default)]
+pub struct Localization {
+    locales: HashMap<String, LocaleHandle>,
+    current: Option<String>,
+}
+
+impl Localization {
+    /// Adds a locale under the given id, like `"en"`, returning the handle it replaced.
+    /// The first locale added becomes the current one.
+    pub fn insert<I: Into<String>>(&mut self, id: I, handle: LocaleHandle) -> Option<LocaleHandle> {
+        let id = id.into();
+        if self.current.is_none() {
+            self.current = Some(id.clone());
+        }
+        self.locales.insert(id, handle)
+    }
+
+    /// Removes a locale. If it was the current one, no locale is current afterwards.
+    pub fn remove(&mut self, id: &str) -> Option<LocaleHandle> {
+        if self.current.as_ref().map(String::as_str) == Some(id) {
+            self.current = None;
+        }
+        self.locales.remove(id)
+    }
+
+    /// Switches to another locale. Returns false and keeps the current locale if no locale
+    /// with this id was added.
+    pub fn set_current(&mut self, id: &str) -> bool {
+        if self.locales.contains_key(id) {
+            self.current = Some(id.to_owned());
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Returns the id of the current locale.
+    pub fn current(&self) -> Option<&str> {
+        self.current.as_ref().map(String::as_str)
+    }
+
+    /// Returns the handle of the current locale.
+    pub fn current_handle(&self) -> Option<&LocaleHandle> {
+        self.current.as_ref().and_then(|id| self.locales.get(id))
+    }
+
+    /// Iterates over the ids of all added locales.
+    pub fn locales(&self) -> impl Iterator<Item = &str> {
+        self.locales.keys().map(String::as_str)
+    }
+
+    /// Formats a message of the current locale.
+    ///
+    /// Returns `None` if there is no current locale, it has not been loaded yet or it does not
+    /// contain the message.
+    pub fn format(
+        &self,
+        storage: &AssetStorage<Locale>,
+        id: &str,
+        args: Option<&HashMap<&str, FluentValue>>,
+    ) -> Option<String> {
+        let locale = storage.get(self.current_handle()?)?;
+        locale.bundle.format(id, args).map(|(message, _)| message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use rayon::{ThreadPool, ThreadPoolBuilder};
+
+    use amethyst_assets::{AssetStorage, Loader, SimpleFormat};
+
+    use super::{Locale, LocaleFormat, LocaleHandle, Localization};
+
+    fn setup() -> (Arc<ThreadPool>, Loader, AssetStorage<Locale>) {
+        let pool = Arc::new(ThreadPoolBuilder::new().num_threads(1).build().unwrap());
+        let loader = Loader::new(".", pool.clone());
+        (pool, loader, AssetStorage::new())
+    }
+
+    fn load(loader: &Loader, storage: &AssetStorage<Locale>, text: &str) -> LocaleHandle {
+        let locale = LocaleFormat.import(text.as_bytes().to_vec(), ()).unwrap();
+        loader.load_from_data(locale, (), storage)
+    }
+
+    #[test]
+    fn first_locale_becomes_current() {
+        let (_, loader, storage) = setup();
+        let mut localization = Localization::default();
+        assert_eq!(localization.current(), None);
+
+        localization.insert("en", load(&loader, &storage, "hello = Hello"));
+        localization.insert("fr", load(&loader, &storage, "hello = Bonjour"));
+        assert_eq!(localization.current(), Some("en"));
+    }
+
+    #[test]
+    fn removing_current_locale_clears_it() {
+        let (_, loader, storage) = setup();
+        let mut localization = Localization::default();
+        localization.insert("en", load(&loader, &storage, "hello = Hello"));
+        localization.insert("fr", load(&loader, &storage, "hello = Bonjour"));
+
+        assert!(localization.remove("fr").is_some());
+        assert_eq!(localization.current(), Some("en"));
+        assert!(localization.remove("en").is_some());
+        assert_eq!(localization.current(), None);
+        assert!(localization.current_handle().is_none());
+    }
+
+    #[test]
+    fn set_current_rejects_unknown_locales() {
+        let (_, loader, storage) = setup();
+        let mut localization = Localization::default();
+        localization.insert("en", load(&loader, &storage, "hello = Hello"));
+        localization.insert("fr", load(&loader, &storage, "hello = Bonjour"));
+
+        assert!(!localization.set_current("de"));
+        assert_eq!(localization.current(), Some("en"));
+        assert!(localization.set_current("fr"));
+        assert_eq!(localization.current(), Some("fr"));
+    }
+
+    #[test]
+    fn format_waits_for_locale_to_load() {
+        let (pool, loader, mut storage) = setup();
+        let mut localization = Localization::default();
+        localization.insert("en", load(&loader, &storage, "hello = Hello"));
+
+        assert_eq!(localization.format(&storage, "hello", None), None);
+
+        storage.process(Into::into, 0, &pool, None);
+        assert_eq!(
+            localization.format(&storage, "hello", None),
+            Some("Hello".to_owned())
+        );
+        assert_eq!(localization.format(&storage, "missing", None), None);
+    }
+}
@@ -5,7 +5,9 @@ use serde::{de::DeserializeOwned, Serialize};
 use amethyst_core::{bundle::SystemBundle, shred::DispatcherBuilder};
 use amethyst_error::{Error, ResultExt};
 
-use crate::{filter::NetFilter, server::ServerConfig, NetSocketSystem};
+use crate::{
+    filter::NetFilter, network_socket::ConnectionValidator, server::ServerConfig, NetSocketSystem,
+};
 
 /// A convenience bundle to create the infrastructure needed to send and receive network messages.
 pub struct NetworkBundle<T> {
@@ -14,6 +16,9 @@ pub struct NetworkBundle<T> {
 
     /// The filters applied on received network events.
     filters: Vec<Box<dyn NetFilter<T>>>,
+
+    /// Checks incoming connection requests.
+    validator: Option<ConnectionValidator>,
 }
 
 impl<T> NetworkBundle<T> {
@@ -30,9 +35,21 @@ impl<T> NetworkBundle<T> {
             udp_recv_addr: receive_addr,
             udp_send_addr: send_addr,
             max_throughput: 5000,
+            ..Default::default()
         };
 
-        NetworkBundle { config, filters }
+        NetworkBundle {
+            config,
+            filters,
+            validator: None,
+        }
+    }
+
+    /// Sets a function deciding which connection requests are accepted.
+    /// See `NetSocketSystem::with_connection_validator`.
+    pub fn with_connection_validator(mut self, validator: ConnectionValidator) -> Self {
+        self.validator = Some(validator);
+        self
     }
 }
 
@@ -42,8 +59,11 @@ where
 {
     /// Build the networking bundle by adding the networking system to the application.
     fn build(self, builder: &mut DispatcherBuilder<'_, '_>) -> Result<(), Error> {
-        let socket_system = NetSocketSystem::<T>::new(self.config, self.filters)
+        let mut socket_system = NetSocketSystem::<T>::new(self.config, self.filters)
             .with_context(|_| Error::from_string("Failed to open network system."))?;
+        if let Some(validator) = self.validator {
+            socket_system = socket_system.with_connection_validator(validator);
+        }
 
         builder.add(socket_system, "net_socket", &[]);
 
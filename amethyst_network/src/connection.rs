@@ -2,7 +2,10 @@
 
 use serde::{Deserialize, Serialize};
 use shrev::{EventChannel, EventIterator, ReaderId};
-use std::net::SocketAddr;
+use std::{
+    net::SocketAddr,
+    time::{Duration, Instant},
+};
 use uuid::Uuid;
 
 use amethyst_core::specs::{Component, VecStorage};
@@ -21,6 +24,10 @@ pub struct NetConnection<E: 'static> {
     pub target_sender: SocketAddr,
     /// The state of the connection.
     pub state: ConnectionState,
+    /// Data sent along with the connection request, like an authentication token.
+    /// See `NetSocketSystem::with_connection_validator`.
+    #[serde(skip)]
+    pub connect_token: Vec<u8>,
     /// The buffer of events to be sent.
     #[serde(skip)]
     pub send_buffer: EventChannel<NetEvent<E>>,
@@ -30,6 +37,21 @@ pub struct NetConnection<E: 'static> {
     /// Private. Used by `NetSocketSystem` to be able to immediately send events upon receiving a new NetConnection.
     #[serde(skip)]
     send_reader: ReaderId<NetEvent<E>>,
+    /// Private. Used by `NetSocketSystem` to time out connection attempts.
+    #[serde(skip)]
+    first_connect_attempt: Option<Instant>,
+    /// Private. Used by `NetSocketSystem` to resend `NetEvent::Connect` while connecting.
+    #[serde(skip)]
+    last_connect_attempt: Option<Instant>,
+    /// Private. Used by `NetSocketSystem` to send heartbeats on idle connections.
+    #[serde(skip)]
+    last_sent: Option<Instant>,
+    /// Private. Used by `NetSocketSystem` to time out silent connections.
+    #[serde(skip)]
+    last_received: Option<Instant>,
+    /// Private. Used by `NetSocketSystem` to flush the send buffer only once after disconnecting.
+    #[serde(skip)]
+    disconnect_flushed: bool,
 }
 
 impl<E: Send + Sync + 'static> NetConnection<E> {
@@ -42,12 +64,24 @@ impl<E: Send + Sync + 'static> NetConnection<E> {
             target_receiver,
             target_sender,
             state: ConnectionState::Connecting,
+            connect_token: Vec::new(),
             send_buffer,
             receive_buffer: EventChannel::<NetEvent<E>>::new(),
             send_reader,
+            first_connect_attempt: None,
+            last_connect_attempt: None,
+            last_sent: None,
+            last_received: None,
+            disconnect_flushed: false,
         }
     }
 
+    /// Sets the data sent along with the connection request.
+    pub fn with_connect_token(mut self, token: Vec<u8>) -> Self {
+        self.connect_token = token;
+        self
+    }
+
     /// Function used ONLY by NetSocketSystem.
     /// Since most users will want to both create the connection and send messages on the same frame,
     /// we need a way to read those. Since the NetSocketSystem runs after the creation of the NetConnection,
@@ -58,6 +92,78 @@ impl<E: Send + Sync + 'static> NetConnection<E> {
     pub fn send_buffer_early_read(&mut self) -> EventIterator<'_, NetEvent<E>> {
         self.send_buffer.read(&mut self.send_reader)
     }
+
+    /// Function used ONLY by NetSocketSystem.
+    /// Returns true if a connection request should be sent, either because none was sent yet or
+    /// because the last one was sent at least `retry_interval` ago.
+    pub(crate) fn connect_attempt_due(&mut self, retry_interval: Duration) -> bool {
+        let now = Instant::now();
+        let due = self
+            .last_connect_attempt
+            .map_or(true, |last| now.duration_since(last) >= retry_interval);
+        if due {
+            self.first_connect_attempt.get_or_insert(now);
+            self.last_connect_attempt = Some(now);
+        }
+        due
+    }
+
+    /// Function used ONLY by NetSocketSystem.
+    /// Returns true if a connection request was sent on this connection.
+    pub(crate) fn connect_requested(&self) -> bool {
+        self.first_connect_attempt.is_some()
+    }
+
+    /// Function used ONLY by NetSocketSystem.
+    /// Returns true if the first connection request was sent more than `timeout` ago.
+    pub(crate) fn connect_timed_out(&self, timeout: Duration) -> bool {
+        self.first_connect_attempt
+            .map_or(false, |first| first.elapsed() > timeout)
+    }
+
+    /// Function used ONLY by NetSocketSystem.
+    /// Records that events were sent on this connection.
+    pub(crate) fn mark_sent(&mut self) {
+        self.last_sent = Some(Instant::now());
+    }
+
+    /// Function used ONLY by NetSocketSystem.
+    /// Records that a packet was received on this connection.
+    pub(crate) fn mark_received(&mut self) {
+        self.last_received = Some(Instant::now());
+    }
+
+    /// Function used ONLY by NetSocketSystem.
+    /// Returns true if nothing was sent for at least `interval`.
+    pub(crate) fn heartbeat_due(&self, interval: Duration) -> bool {
+        self.last_sent
+            .map_or(true, |last| last.elapsed() >= interval)
+    }
+
+    /// Function used ONLY by NetSocketSystem.
+    /// Returns true if nothing was received for more than `timeout`.
+    pub(crate) fn idle_timed_out(&self, timeout: Duration) -> bool {
+        self.last_received
+            .map_or(false, |last| last.elapsed() > timeout)
+    }
+
+    /// Function used ONLY by NetSocketSystem.
+    /// Returns true the first time it is called, when the events written before disconnecting
+    /// should be sent.
+    pub(crate) fn disconnect_flush_due(&mut self) -> bool {
+        !std::mem::replace(&mut self.disconnect_flushed, true)
+    }
+
+    /// Closes the connection, notifying the remote end with a `NetEvent::Disconnect`.
+    ///
+    /// Events written to the send buffer before calling this are still sent, events written
+    /// afterwards are dropped.
+    pub fn disconnect<R: Into<String>>(&mut self, reason: R) {
+        self.send_buffer.single_write(NetEvent::Disconnect {
+            reason: reason.into(),
+        });
+        self.state = ConnectionState::Disconnected;
+    }
 }
 
 impl<E> PartialEq for NetConnection<E> {
@@ -84,6 +190,8 @@ pub enum ConnectionState {
     Connecting,
     /// The connection has been dropped.
     Disconnected,
+    /// The connection could not be established in time.
+    TimedOut,
 }
 
 /// A network identity. It can represent either a client or a server.
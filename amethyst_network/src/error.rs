@@ -25,6 +25,22 @@ pub enum Error {
     /// Error that could occur when sending an `ServerSocketEvent` to some channel.
     #[error(display = "Channel send error occurred")]
     ChannelSendError(#[cause] mpsc::SendError<ServerSocketEvent>),
+    /// Error that occurs when a received payload has an unknown header or can't be decompressed.
+    #[error(display = "Received a malformed payload")]
+    MalformedPayload,
+    /// Error that occurs when a received compressed payload declares a decompressed size above
+    /// `ServerConfig::max_decompressed_size`.
+    #[error(display = "Received a payload of {} bytes, which is too large", _0)]
+    PayloadTooLarge(usize),
+    /// Error that occurs when creating a room with a name that is already taken.
+    #[error(display = "Room {} already exists", _0)]
+    RoomExists(String),
+    /// Error that occurs when using a room that does not exist.
+    #[error(display = "Room {} does not exist", _0)]
+    NoSuchRoom(String),
+    /// Error that occurs when joining a room that has reached its capacity.
+    #[error(display = "Room {} is full", _0)]
+    RoomFull(String),
     #[error(display = "Some error has occurred")]
     #[doc(hidden)]
     __Nonexhaustive,
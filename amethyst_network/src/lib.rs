@@ -8,53 +8,196 @@ pub use crate::{
     error::Result,
     filter::{FilterConnected, NetFilter},
     net_event::NetEvent,
-    network_socket::NetSocketSystem,
+    network_socket::{ConnectionValidator, NetSocketSystem},
+    rooms::{Room, RoomEvent, Rooms},
     server::{Host, ServerConfig, ServerSocketEvent},
+    snapshot::{Interpolate, SnapshotBuffer, SnapshotInterpolationSystem},
+    stats::{ConnectionStats, NetworkStats},
 };
 
-use std::{net::SocketAddr, sync::mpsc::SyncSender};
+use std::{borrow::Cow, net::SocketAddr, slice, sync::mpsc::SyncSender};
 
-use bincode::{deserialize, serialize};
+use bincode::{deserialize, serialize_into};
 use laminar::Packet;
 use log::error;
 use serde::{de::DeserializeOwned, Serialize};
 
+use crate::error::Error;
+
 mod bundle;
 mod connection;
 mod error;
 mod filter;
 mod net_event;
 mod network_socket;
+mod rooms;
 mod server;
+mod snapshot;
+mod stats;
 mod test;
 
+// Header byte in front of every payload, telling how the rest of it is encoded.
+const PAYLOAD_RAW: u8 = 0;
+const PAYLOAD_LZ4: u8 = 1;
+
+/// Sends events to the target NetConnection using the provided network Socket.
+/// The socket has to be bound.
+///
+/// The events are combined into as few packets as possible, each holding events with a
+/// serialized size of at most `max_batch_size` bytes. Larger events are sent on their own.
+/// Packets whose serialized events take up at least `compression_threshold` bytes are
+/// compressed.
+///
+/// Returns the number of payload bytes handed to the socket.
+pub fn send_events<T>(
+    events: &[NetEvent<T>],
+    addr: SocketAddr,
+    sender: &SyncSender<ServerSocketEvent>,
+    compression_threshold: Option<usize>,
+    max_batch_size: usize,
+) -> u64
+where
+    T: Serialize,
+{
+    let mut bytes_sent = 0;
+    // The serialized events of the current batch, back to back.
+    let mut batch = Vec::new();
+    let mut batch_len = 0;
+    for event in events {
+        let event_start = batch.len();
+        if let Err(e) = serialize_into(&mut batch, event) {
+            error!("Failed to serialize the event, skipping it: {}", e);
+            batch.truncate(event_start);
+            continue;
+        }
+        if batch_len > 0 && batch.len() > max_batch_size {
+            bytes_sent += send_batch(
+                batch_len,
+                &batch[..event_start],
+                addr,
+                sender,
+                compression_threshold,
+            );
+            batch.drain(..event_start);
+            batch_len = 0;
+        }
+        batch_len += 1;
+    }
+    if batch_len > 0 {
+        bytes_sent += send_batch(batch_len, &batch, addr, sender, compression_threshold);
+    }
+    bytes_sent
+}
+
 /// Sends an event to the target NetConnection using the provided network Socket.
 /// The socket has to be bound.
+///
+/// The event is sent uncompressed in a packet of its own.
 pub fn send_event<T>(event: NetEvent<T>, addr: SocketAddr, sender: &SyncSender<ServerSocketEvent>)
 where
     T: Serialize,
 {
-    let ser = serialize(&event);
-    match ser {
-        Ok(s) => {
-            let slice = s.as_slice();
-            // send an unreliable `Packet` from laminar which is basically just a bare UDP packet.
-            match sender.send(ServerSocketEvent::Packet(Packet::unreliable(
-                addr,
-                slice.to_owned(),
-            ))) {
-                Ok(_qty) => {}
-                Err(e) => error!("Failed to send data to network socket: {}", e),
+    send_events(
+        slice::from_ref(&event),
+        addr,
+        sender,
+        None,
+        usize::max_value(),
+    );
+}
+
+/// Sends an event to the target NetConnection using the provided network Socket.
+/// The socket has to be bound.
+///
+/// The event is sent in a packet of its own, which is compressed if the serialized event takes
+/// up at least `compression_threshold` bytes.
+pub fn send_event_with_compression<T>(
+    event: NetEvent<T>,
+    addr: SocketAddr,
+    sender: &SyncSender<ServerSocketEvent>,
+    compression_threshold: Option<usize>,
+) where
+    T: Serialize,
+{
+    send_events(
+        slice::from_ref(&event),
+        addr,
+        sender,
+        compression_threshold,
+        usize::max_value(),
+    );
+}
+
+// Sends `len` events, serialized back to back in `events`, in a single packet. Returns the size
+// of its payload or 0 if sending failed.
+fn send_batch(
+    len: usize,
+    events: &[u8],
+    addr: SocketAddr,
+    sender: &SyncSender<ServerSocketEvent>,
+    compression_threshold: Option<usize>,
+) -> u64 {
+    // Lay the packet out like a serialized `Vec<NetEvent>`: the length followed by the events.
+    let mut data = Vec::with_capacity(8 + events.len());
+    serialize_into(&mut data, &(len as u64))
+        .expect("Unreachable: Serializing an integer into a Vec can't fail");
+    data.extend_from_slice(events);
+
+    let payload = encode_payload(data, compression_threshold);
+    let payload_len = payload.len() as u64;
+    // send an unreliable `Packet` from laminar which is basically just a bare UDP packet.
+    match sender.send(ServerSocketEvent::Packet(Packet::unreliable(addr, payload))) {
+        Ok(_qty) => payload_len,
+        Err(e) => {
+            error!("Failed to send data to network socket: {}", e);
+            0
+        }
+    }
+}
+
+// Prepends the payload header, compressing the data if it is large enough and compression
+// actually makes it smaller.
+fn encode_payload(data: Vec<u8>, compression_threshold: Option<usize>) -> Vec<u8> {
+    if compression_threshold.map_or(false, |threshold| data.len() >= threshold) {
+        // The decompressed size is prepended, so the receiver can check it before allocating.
+        let compressed = lz4_flex::block::compress_prepend_size(&data);
+        if compressed.len() < data.len() {
+            let mut payload = Vec::with_capacity(compressed.len() + 1);
+            payload.push(PAYLOAD_LZ4);
+            payload.extend(compressed);
+            return payload;
+        }
+    }
+
+    let mut payload = Vec::with_capacity(data.len() + 1);
+    payload.push(PAYLOAD_RAW);
+    payload.extend(data);
+    payload
+}
+
+// Strips the payload header, decompressing the data if needed. Compressed payloads declaring a
+// decompressed size above `max_size` are rejected.
+fn decode_payload(payload: &[u8], max_size: usize) -> Result<Cow<'_, [u8]>> {
+    match payload.split_first() {
+        Some((&PAYLOAD_RAW, data)) => Ok(Cow::Borrowed(data)),
+        Some((&PAYLOAD_LZ4, data)) if data.len() >= 4 => {
+            let size = u32::from_le_bytes([data[0], data[1], data[2], data[3]]) as usize;
+            if size > max_size {
+                return Err(Error::PayloadTooLarge(size));
             }
+            lz4_flex::block::decompress_size_prepended(data)
+                .map(Cow::Owned)
+                .map_err(|_| Error::MalformedPayload)
         }
-        Err(e) => error!("Failed to serialize the event: {}", e),
+        _ => Err(Error::MalformedPayload),
     }
 }
 
-// Attempts to deserialize an event from the raw byte data.
-fn deserialize_event<T>(data: &[u8]) -> Result<NetEvent<T>>
+// Attempts to deserialize the events of a packet from the raw byte data.
+fn deserialize_events<T>(payload: &[u8], max_size: usize) -> Result<Vec<NetEvent<T>>>
 where
     T: DeserializeOwned,
 {
-    Ok(deserialize::<NetEvent<T>>(data)?)
+    let data = decode_payload(payload, max_size)?;
+    Ok(deserialize::<Vec<NetEvent<T>>>(&data)?)
 }
@@ -13,6 +13,11 @@ pub enum NetEvent<T> {
     Connect {
         /// The client uuid.
         client_uuid: Uuid,
+        /// Application defined data checked by the server before accepting the connection,
+        /// like an authentication token.
+        token: Vec<u8>,
+        /// The version of the application protocol used by the client.
+        protocol_version: u32,
     },
     /// Reply to the client that the connection has been accepted.
     Connected {
@@ -24,6 +29,15 @@ pub enum NetEvent<T> {
         /// The reason of the refusal.
         reason: String,
     },
+    /// Written to the receive buffer when a peer asks to connect using a different protocol
+    /// version. It is also sent to the peer along with a `ConnectionRefused`, and marks the
+    /// connection as `Disconnected` on both sides.
+    VersionMismatch {
+        /// The protocol version of the peer.
+        theirs: u32,
+        /// The local protocol version.
+        ours: u32,
+    },
     /// Tell the server that the client is disconnecting.
     Disconnect {
         /// The reason of the disconnection.
@@ -34,6 +48,9 @@ pub enum NetEvent<T> {
         /// The reason of the disconnection.
         reason: String,
     },
+    /// Keeps an idle connection alive. Handled by the `NetSocketSystem` and not written to the
+    /// receive buffer.
+    Heartbeat,
     /// A simple text message event.
     TextMessage {
         /// The message.
@@ -2,26 +2,30 @@
 
 use std::{
     clone::Clone,
+    collections::HashSet,
     net::SocketAddr,
     sync::{
         mpsc::{self, Receiver, Sender},
         Arc, Mutex,
     },
     thread,
+    time::Instant,
 };
 
-use amethyst_core::specs::{Join, Resources, System, SystemData, WriteStorage};
+use amethyst_core::specs::{Join, Read, Resources, System, SystemData, Write, WriteStorage};
 
 use laminar::Packet;
-use log::{error, warn};
+use log::{debug, error, warn};
 use serde::{de::DeserializeOwned, Serialize};
+use uuid::Uuid;
 
 use super::{
-    deserialize_event,
+    deserialize_events,
     error::Result,
-    send_event,
+    send_events,
     server::{Host, ReceiveHandler, SendHandler, ServerConfig, ServerSocketEvent},
-    ConnectionState, NetConnection, NetEvent, NetFilter,
+    ConnectionState, ConnectionStats, NetConnection, NetEvent, NetFilter, NetIdentity,
+    NetworkStats,
 };
 
 enum InternalSocketEvent<E> {
@@ -42,6 +46,36 @@ enum InternalSocketEvent<E> {
 ///
 /// If both a connection (Connect or Connected) event is received at the same time as another event from the same connection,
 /// only the connection event will be considered and rest will be filtered out.
+///
+/// Connections in the `Connecting` state periodically send a `NetEvent::Connect` with the uuid
+/// of the local `NetIdentity` resource, their `connect_token` and `ServerConfig::protocol_version`.
+/// A peer receiving it refuses it if the protocol versions differ, writing a
+/// `NetEvent::VersionMismatch` to its receive buffer and sending one back along with the refusal,
+/// and checks it with the connection validator, if one is set. If it is accepted, the peer marks its own
+/// connection to the sender as `Connected` and answers with `NetEvent::Connected`, which
+/// completes the handshake on the other side unless that side has a connection validator itself.
+/// Otherwise it marks its connection as `Disconnected` and answers with
+/// `NetEvent::ConnectionRefused`, which marks the connection as `Disconnected` on the other side
+/// too and is passed on to the receive buffer. The `Connect` or `Connected` event which makes a
+/// connection `Connected` is written to its receive buffer, so each side learns about the new
+/// connection once. A `Connect` received on a connection which is already `Connected` is not
+/// validated again, it is only answered with another `Connected`.
+///
+/// If the handshake does not complete within `ServerConfig::connect_timeout`, the connection
+/// state becomes `TimedOut` and a `NetEvent::Disconnected` is written to its receive buffer.
+/// `TimedOut` connections are flushed once like `Disconnected` ones, without affecting the other
+/// connections.
+///
+/// Events other than the connection events are only written to the receive buffer of a
+/// `Connected` connection and dropped otherwise.
+///
+/// Receiving `NetEvent::Disconnect` or `NetEvent::Disconnected` marks the connection as
+/// `Disconnected`; see `NetConnection::disconnect` for closing a connection from this side.
+///
+/// Connected connections which have not sent anything for `ServerConfig::heartbeat_interval`
+/// send a `NetEvent::Heartbeat`. If nothing is received on a connected connection for
+/// `ServerConfig::idle_timeout`, its state becomes `TimedOut` and a `NetEvent::Disconnected` is
+/// written to its receive buffer.
 // TODO: add Unchecked Event type list. Those events will be let pass the client connected filter (Example: NetEvent::Connect).
 // Current behaviour: hardcoded passthrough of Connect and Connected events.
 pub struct NetSocketSystem<E: 'static>
@@ -54,9 +88,18 @@ where
     transport_sender: Sender<InternalSocketEvent<E>>,
     // receiver from which you can read received packets.
     transport_receiver: Receiver<Packet>,
+    // receiver from which you can read how many bytes were sent to each endpoint.
+    bytes_sent_receiver: Receiver<(SocketAddr, u64)>,
     config: ServerConfig,
+    // checks incoming connection requests.
+    validator: Option<ConnectionValidator>,
 }
 
+/// Checks the sender address, uuid and token of a connection request, returning the reason for
+/// refusing it as error.
+pub type ConnectionValidator =
+    Box<dyn Fn(SocketAddr, Uuid, &[u8]) -> std::result::Result<(), String> + Send + Sync>;
+
 impl<E> NetSocketSystem<E>
 where
     E: Serialize + PartialEq + Send + 'static,
@@ -73,27 +116,57 @@ where
         let udp_send_handle = server.udp_send_handle();
         let udp_receive_handle = server.udp_receive_handle();
 
-        let server_sender = NetSocketSystem::<E>::start_sending(udp_send_handle);
+        let (bytes_sent_sender, bytes_sent_receiver) = mpsc::channel();
+        let server_sender = NetSocketSystem::<E>::start_sending(
+            udp_send_handle,
+            bytes_sent_sender,
+            config.compression_threshold,
+            config.max_batch_size,
+        );
         let server_receiver = NetSocketSystem::<E>::start_receiving(udp_receive_handle);
 
         Ok(NetSocketSystem {
             filters,
             transport_sender: server_sender,
             transport_receiver: server_receiver,
+            bytes_sent_receiver,
             config,
+            validator: None,
         })
     }
 
-    /// Start a thread to send all queued packets.
-    fn start_sending(sender: Arc<SendHandler>) -> Sender<InternalSocketEvent<E>> {
+    /// Sets a function deciding which connection requests are accepted.
+    /// By default, all requests are accepted.
+    pub fn with_connection_validator(mut self, validator: ConnectionValidator) -> Self {
+        self.validator = Some(validator);
+        self
+    }
+
+    /// Start a thread to send all queued packets, reporting the number of bytes sent to each
+    /// target on `bytes_sent`.
+    fn start_sending(
+        sender: Arc<SendHandler>,
+        bytes_sent: Sender<(SocketAddr, u64)>,
+        compression_threshold: Option<usize>,
+        max_batch_size: usize,
+    ) -> Sender<InternalSocketEvent<E>> {
         let (tx, send_queue) = mpsc::channel();
 
-        thread::spawn(move || loop {
-            for control_event in send_queue.try_iter() {
+        // Runs until the system is dropped, which sends `Stop` and closes the channel.
+        thread::spawn(move || {
+            for control_event in send_queue.iter() {
                 match control_event {
                     InternalSocketEvent::SendEvents { target, events } => {
-                        for ev in events {
-                            send_event(ev, target, &sender.get_sender());
+                        let bytes = send_events(
+                            &events,
+                            target,
+                            &sender.get_sender(),
+                            compression_threshold,
+                            max_batch_size,
+                        );
+                        if bytes > 0 {
+                            // The system only goes away together with this thread.
+                            let _ = bytes_sent.send((target, bytes));
                         }
                     }
                     InternalSocketEvent::Stop => {
@@ -142,29 +215,243 @@ where
     }
 }
 
+impl<E> NetSocketSystem<E>
+where
+    E: Send + Sync + Serialize + Clone + DeserializeOwned + PartialEq + 'static,
+{
+    /// Applies a received event to its connection, answering connection requests.
+    fn handle_event(
+        &self,
+        event: NetEvent<E>,
+        source: SocketAddr,
+        net_connection: &mut NetConnection<E>,
+        connection_stats: &mut ConnectionStats,
+        identity: &NetIdentity,
+    ) {
+        match event {
+            NetEvent::Connect { .. }
+                if net_connection.state != ConnectionState::Connecting
+                    && net_connection.state != ConnectionState::Connected =>
+            {
+                // Closed connections stay closed, even if the peer keeps asking.
+                self.send_answers(
+                    net_connection,
+                    connection_stats,
+                    vec![NetEvent::ConnectionRefused {
+                        reason: "Connection is closed".to_owned(),
+                    }],
+                );
+            }
+            NetEvent::Connect { .. } if net_connection.state == ConnectionState::Connected => {
+                // The peer was already accepted and probably missed our answer. Validating again
+                // could drop a live connection, for example with single-use tokens.
+                self.send_answers(
+                    net_connection,
+                    connection_stats,
+                    vec![NetEvent::Connected {
+                        server_uuid: identity.uuid,
+                    }],
+                );
+            }
+            NetEvent::Connect {
+                client_uuid,
+                token,
+                protocol_version,
+            } => {
+                let ours = self.config.protocol_version;
+                let mut answers = Vec::new();
+                let verdict = if protocol_version != ours {
+                    net_connection
+                        .receive_buffer
+                        .single_write(NetEvent::VersionMismatch {
+                            theirs: protocol_version,
+                            ours,
+                        });
+                    // Tell the peer as well, from its point of view.
+                    answers.push(NetEvent::VersionMismatch {
+                        theirs: ours,
+                        ours: protocol_version,
+                    });
+                    Err(format!(
+                        "Protocol version mismatch: expected {}, got {}",
+                        ours, protocol_version
+                    ))
+                } else {
+                    self.validator
+                        .as_ref()
+                        .map_or(Ok(()), |validator| validator(source, client_uuid, &token))
+                };
+                answers.push(match verdict {
+                    Ok(()) => {
+                        net_connection.state = ConnectionState::Connected;
+                        // Let the application know that the peer joined.
+                        net_connection
+                            .receive_buffer
+                            .single_write(NetEvent::Connect {
+                                client_uuid,
+                                token,
+                                protocol_version,
+                            });
+                        NetEvent::Connected {
+                            server_uuid: identity.uuid,
+                        }
+                    }
+                    Err(reason) => {
+                        warn!("Refused connection from {}: {}", source, reason);
+                        net_connection.state = ConnectionState::Disconnected;
+                        NetEvent::ConnectionRefused { reason }
+                    }
+                });
+                // Answer every request, since a previous answer might have been lost.
+                self.send_answers(net_connection, connection_stats, answers);
+            }
+            ev @ NetEvent::Connected { .. } => {
+                // Only an answer to our own request completes the handshake. With a validator,
+                // connections are only accepted by validating the `Connect` of the peer.
+                if net_connection.state == ConnectionState::Connecting
+                    && net_connection.connect_requested()
+                    && self.validator.is_none()
+                {
+                    net_connection.state = ConnectionState::Connected;
+                    net_connection.receive_buffer.single_write(ev);
+                }
+            }
+            NetEvent::Heartbeat => {}
+            ev @ NetEvent::ConnectionRefused { .. }
+            | ev @ NetEvent::VersionMismatch { .. }
+            | ev @ NetEvent::Disconnect { .. }
+            | ev @ NetEvent::Disconnected { .. } => {
+                net_connection.state = ConnectionState::Disconnected;
+                net_connection.receive_buffer.single_write(ev);
+            }
+            ev => {
+                // Events from peers which have not been accepted are dropped.
+                if net_connection.state == ConnectionState::Connected {
+                    net_connection.receive_buffer.single_write(ev);
+                } else {
+                    debug!("Dropped an event from {}, which is not connected", source);
+                }
+            }
+        }
+    }
+
+    /// Sends the answers to a connection request right away.
+    fn send_answers(
+        &self,
+        net_connection: &NetConnection<E>,
+        connection_stats: &mut ConnectionStats,
+        answers: Vec<NetEvent<E>>,
+    ) {
+        connection_stats.messages_sent += answers.len() as u64;
+        self.transport_sender
+            .send(InternalSocketEvent::SendEvents {
+                target: net_connection.target_receiver,
+                events: answers,
+            })
+            .expect("Unreachable: Channel will be alive until a stop event is sent");
+    }
+}
+
+impl<E> Drop for NetSocketSystem<E>
+where
+    E: PartialEq + 'static,
+{
+    fn drop(&mut self) {
+        // The sending thread might have stopped already if the socket failed.
+        let _ = self.transport_sender.send(InternalSocketEvent::Stop);
+    }
+}
+
 impl<'a, E> System<'a> for NetSocketSystem<E>
 where
     E: Send + Sync + Serialize + Clone + DeserializeOwned + PartialEq + 'static,
 {
-    type SystemData = (WriteStorage<'a, NetConnection<E>>);
+    type SystemData = (
+        WriteStorage<'a, NetConnection<E>>,
+        Read<'a, NetIdentity>,
+        Write<'a, NetworkStats>,
+    );
+
+    fn run(&mut self, (mut net_connections, identity, mut stats): Self::SystemData) {
+        for (target, bytes) in self.bytes_sent_receiver.try_iter() {
+            if let Some(connection_stats) = stats.existing_connection_mut(&target) {
+                connection_stats.bytes_sent += bytes;
+            }
+        }
 
-    fn run(&mut self, mut net_connections: Self::SystemData) {
         for net_connection in (&mut net_connections).join() {
             let target = net_connection.target_receiver;
 
+            if net_connection.state == ConnectionState::Connecting
+                && net_connection.connect_timed_out(self.config.connect_timeout)
+            {
+                warn!("Connection to {} timed out", target);
+                net_connection.state = ConnectionState::TimedOut;
+                net_connection
+                    .receive_buffer
+                    .single_write(NetEvent::Disconnected {
+                        reason: "Connection timed out".to_owned(),
+                    });
+            }
+
+            if net_connection.state == ConnectionState::Connected
+                && self
+                    .config
+                    .idle_timeout
+                    .map_or(false, |timeout| net_connection.idle_timed_out(timeout))
+            {
+                warn!("Connection to {} went silent", target);
+                net_connection.state = ConnectionState::TimedOut;
+                net_connection
+                    .receive_buffer
+                    .single_write(NetEvent::Disconnected {
+                        reason: "Connection went silent".to_owned(),
+                    });
+            }
+
             if net_connection.state == ConnectionState::Connected
                 || net_connection.state == ConnectionState::Connecting
             {
+                let mut events = Vec::new();
+                if net_connection.state == ConnectionState::Connecting
+                    && net_connection.connect_attempt_due(self.config.connect_retry_interval)
+                {
+                    events.push(NetEvent::Connect {
+                        client_uuid: identity.uuid,
+                        token: net_connection.connect_token.clone(),
+                        protocol_version: self.config.protocol_version,
+                    });
+                }
+                events.extend(net_connection.send_buffer_early_read().cloned());
+                if events.is_empty()
+                    && net_connection.state == ConnectionState::Connected
+                    && self
+                        .config
+                        .heartbeat_interval
+                        .map_or(false, |interval| net_connection.heartbeat_due(interval))
+                {
+                    events.push(NetEvent::Heartbeat);
+                }
+                if !events.is_empty() {
+                    net_connection.mark_sent();
+                }
+                stats.connection_mut(target).messages_sent += events.len() as u64;
+
                 self.transport_sender
-                    .send(InternalSocketEvent::SendEvents {
-                        target,
-                        events: net_connection.send_buffer_early_read().cloned().collect(),
-                    })
+                    .send(InternalSocketEvent::SendEvents { target, events })
                     .expect("Unreachable: Channel will be alive until a stop event is sent");
-            } else if net_connection.state == ConnectionState::Disconnected {
-                self.transport_sender
-                    .send(InternalSocketEvent::Stop)
-                    .expect("Already sent a stop event to the channel");
+            } else if net_connection.state == ConnectionState::Disconnected
+                || net_connection.state == ConnectionState::TimedOut
+            {
+                // Flush what was written before disconnecting, like the `Disconnect` event, once.
+                // Anything written afterwards is read and dropped.
+                let events: Vec<_> = net_connection.send_buffer_early_read().cloned().collect();
+                if net_connection.disconnect_flush_due() {
+                    stats.connection_mut(target).messages_sent += events.len() as u64;
+                    self.transport_sender
+                        .send(InternalSocketEvent::SendEvents { target, events })
+                        .expect("Unreachable: Channel will be alive until a stop event is sent");
+                }
             }
         }
 
@@ -172,16 +459,35 @@ where
             // Get the NetConnection from the source
             for net_connection in (&mut net_connections).join() {
                 if net_connection.target_sender == raw_event.addr() {
+                    net_connection.mark_received();
+                    let connection_stats = stats.connection_mut(net_connection.target_receiver);
+                    connection_stats.bytes_received += raw_event.payload().len() as u64;
+
                     // Get the event
-                    match deserialize_event::<E>(raw_event.payload()) {
-                        Ok(ev) => {
-                            net_connection.receive_buffer.single_write(ev);
+                    match deserialize_events::<E>(
+                        raw_event.payload(),
+                        self.config.max_decompressed_size,
+                    ) {
+                        Ok(events) => {
+                            connection_stats.messages_received += events.len() as u64;
+                            for event in events {
+                                self.handle_event(
+                                    event,
+                                    raw_event.addr(),
+                                    net_connection,
+                                    connection_stats,
+                                    &identity,
+                                );
+                            }
+                        }
+                        Err(e) => {
+                            connection_stats.invalid_messages += 1;
+                            error!(
+                                "Failed to deserialize an incoming network event: {} From source: {:?}",
+                                e,
+                                raw_event.addr()
+                            );
                         }
-                        Err(e) => error!(
-                            "Failed to deserialize an incoming network event: {} From source: {:?}",
-                            e,
-                            raw_event.addr()
-                        ),
                     }
                 } else {
                     warn!("Received packet from unknown source");
@@ -195,6 +501,13 @@ where
                 break;
             }
         }
+
+        let live: HashSet<_> = net_connections
+            .join()
+            .map(|net_connection| net_connection.target_receiver)
+            .collect();
+        stats.retain(|addr| live.contains(addr));
+        stats.update_rates(Instant::now());
     }
 
     fn setup(&mut self, res: &mut Resources) {
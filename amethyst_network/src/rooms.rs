@@ -0,0 +1,250 @@
+//! Server side grouping of connections into rooms.
+
+use std::collections::{hash_map::Entry, HashMap, HashSet};
+
+use shrev::{EventChannel, EventIterator, ReaderId};
+
+use amethyst_core::specs::{Entity, WriteStorage};
+
+use crate::{error::Error, NetConnection, NetEvent, Result};
+
+/// Changes made to the `Rooms` resource.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RoomEvent {
+    /// A room was created.
+    Created(String),
+    /// A room was removed, together with all of its memberships.
+    Removed(String),
+    /// A member joined a room.
+    Joined {
+        /// The name of the room.
+        room: String,
+        /// The entity of the member's `NetConnection`.
+        member: Entity,
+    },
+    /// A member left a room.
+    Left {
+        /// The name of the room.
+        room: String,
+        /// The entity of the member's `NetConnection`.
+        member: Entity,
+    },
+}
+
+/// A named group of connections.
+#[derive(Debug, Clone, Default)]
+pub struct Room {
+    members: HashSet<Entity>,
+    capacity: Option<usize>,
+}
+
+impl Room {
+    /// Iterates over the entities of the `NetConnection`s in this room.
+    pub fn members(&self) -> impl Iterator<Item = Entity> + '_ {
+        self.members.iter().cloned()
+    }
+
+    /// Returns true if the entity is a member of this room.
+    pub fn contains(&self, member: Entity) -> bool {
+        self.members.contains(&member)
+    }
+
+    /// Returns the number of members.
+    pub fn len(&self) -> usize {
+        self.members.len()
+    }
+
+    /// Returns true if the room has no members.
+    pub fn is_empty(&self) -> bool {
+        self.members.is_empty()
+    }
+
+    /// Returns the maximum number of members, if there is one.
+    pub fn capacity(&self) -> Option<usize> {
+        self.capacity
+    }
+
+    /// Returns true if no more members can join.
+    pub fn is_full(&self) -> bool {
+        self.capacity
+            .map_or(false, |capacity| self.len() >= capacity)
+    }
+}
+
+/// Resource grouping connections into named rooms, like game lobbies or matches.
+///
+/// Members are the entities holding the `NetConnection` of a client. All changes are reported
+/// as `RoomEvent`s, which can be read after registering a reader with `register_reader`.
+/// Remember to call `leave_all` when a client disconnects.
+#[derive(Default)]
+pub struct Rooms {
+    rooms: HashMap<String, Room>,
+    events: EventChannel<RoomEvent>,
+}
+
+impl Rooms {
+    /// Creates an empty room. `capacity` limits the number of members, if given.
+    pub fn create<N: Into<String>>(&mut self, name: N, capacity: Option<usize>) -> Result<()> {
+        let name = name.into();
+        match self.rooms.entry(name.clone()) {
+            Entry::Occupied(_) => Err(Error::RoomExists(name)),
+            Entry::Vacant(entry) => {
+                entry.insert(Room {
+                    members: HashSet::new(),
+                    capacity,
+                });
+                self.events.single_write(RoomEvent::Created(name));
+                Ok(())
+            }
+        }
+    }
+
+    /// Removes a room, returning it if it existed.
+    pub fn remove(&mut self, name: &str) -> Option<Room> {
+        let room = self.rooms.remove(name)?;
+        self.events
+            .single_write(RoomEvent::Removed(name.to_owned()));
+        Some(room)
+    }
+
+    /// Returns the room with the given name.
+    pub fn get(&self, name: &str) -> Option<&Room> {
+        self.rooms.get(name)
+    }
+
+    /// Iterates over all rooms and their names.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Room)> {
+        self.rooms.iter().map(|(name, room)| (name.as_str(), room))
+    }
+
+    /// Adds a member to a room. Joining a room twice has no effect.
+    pub fn join(&mut self, name: &str, member: Entity) -> Result<()> {
+        let room = match self.rooms.get_mut(name) {
+            Some(room) => room,
+            None => return Err(Error::NoSuchRoom(name.to_owned())),
+        };
+        if room.contains(member) {
+            return Ok(());
+        }
+        if room.is_full() {
+            return Err(Error::RoomFull(name.to_owned()));
+        }
+        room.members.insert(member);
+        self.events.single_write(RoomEvent::Joined {
+            room: name.to_owned(),
+            member,
+        });
+        Ok(())
+    }
+
+    /// Removes a member from a room. Returns false if it wasn't a member.
+    pub fn leave(&mut self, name: &str, member: Entity) -> bool {
+        let left = self
+            .rooms
+            .get_mut(name)
+            .map_or(false, |room| room.members.remove(&member));
+        if left {
+            self.events.single_write(RoomEvent::Left {
+                room: name.to_owned(),
+                member,
+            });
+        }
+        left
+    }
+
+    /// Removes a member from all rooms.
+    pub fn leave_all(&mut self, member: Entity) {
+        let events = &mut self.events;
+        for (name, room) in &mut self.rooms {
+            if room.members.remove(&member) {
+                events.single_write(RoomEvent::Left {
+                    room: name.clone(),
+                    member,
+                });
+            }
+        }
+    }
+
+    /// Iterates over the names of the rooms an entity is a member of.
+    pub fn rooms_of(&self, member: Entity) -> impl Iterator<Item = &str> {
+        self.rooms
+            .iter()
+            .filter(move |(_, room)| room.contains(member))
+            .map(|(name, _)| name.as_str())
+    }
+
+    /// Writes an event to the send buffer of every member of a room.
+    pub fn broadcast<E>(
+        &self,
+        name: &str,
+        event: NetEvent<E>,
+        connections: &mut WriteStorage<'_, NetConnection<E>>,
+    ) -> Result<()>
+    where
+        E: Clone + Send + Sync + 'static,
+    {
+        let room = match self.rooms.get(name) {
+            Some(room) => room,
+            None => return Err(Error::NoSuchRoom(name.to_owned())),
+        };
+        for member in room.members() {
+            if let Some(connection) = connections.get_mut(member) {
+                connection.send_buffer.single_write(event.clone());
+            }
+        }
+        Ok(())
+    }
+
+    /// Registers a reader for `RoomEvent`s.
+    pub fn register_reader(&mut self) -> ReaderId<RoomEvent> {
+        self.events.register_reader()
+    }
+
+    /// Reads the `RoomEvent`s written since the last read with this reader.
+    pub fn read_events(&self, reader: &mut ReaderId<RoomEvent>) -> EventIterator<'_, RoomEvent> {
+        self.events.read(reader)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use amethyst_core::specs::{Builder, World};
+
+    use super::{RoomEvent, Rooms};
+
+    #[test]
+    fn join_respects_capacity() {
+        let mut world = World::new();
+        let a = world.create_entity().build();
+        let b = world.create_entity().build();
+
+        let mut rooms = Rooms::default();
+        let mut reader = rooms.register_reader();
+        rooms.create("lobby", Some(1)).unwrap();
+        assert!(rooms.create("lobby", None).is_err());
+
+        rooms.join("lobby", a).unwrap();
+        assert!(rooms.join("lobby", b).is_err());
+        assert!(rooms.join("missing", b).is_err());
+        assert_eq!(rooms.rooms_of(a).collect::<Vec<_>>(), vec!["lobby"]);
+
+        rooms.leave_all(a);
+        assert!(rooms.get("lobby").unwrap().is_empty());
+
+        let events: Vec<_> = rooms.read_events(&mut reader).cloned().collect();
+        assert_eq!(
+            events,
+            vec![
+                RoomEvent::Created("lobby".to_owned()),
+                RoomEvent::Joined {
+                    room: "lobby".to_owned(),
+                    member: a,
+                },
+                RoomEvent::Left {
+                    room: "lobby".to_owned(),
+                    member: a,
+                },
+            ]
+        );
+    }
+}
@@ -1,4 +1,4 @@
-use std::net::SocketAddr;
+use std::{net::SocketAddr, time::Duration};
 
 #[derive(Clone, Debug)]
 /// The configuration used for the networking system.
@@ -11,6 +11,37 @@ pub struct ServerConfig {
     /// This value is meant for preventing some loops to read infinitely long when many packets are send and received.
     /// This value is by default 5000.
     pub max_throughput: u16,
+    /// How long to wait for an answer to a `NetEvent::Connect` before sending it again.
+    /// This value is by default one second.
+    pub connect_retry_interval: Duration,
+    /// How long to wait for a connection to be established before giving up on it.
+    /// This value is by default ten seconds.
+    pub connect_timeout: Duration,
+    /// Packets whose serialized events take up at least this many bytes are compressed with LZ4
+    /// before sending. `None` disables compression.
+    /// This value is by default 512 bytes.
+    pub compression_threshold: Option<usize>,
+    /// Compressed payloads declaring a larger decompressed size than this many bytes are
+    /// rejected without being decompressed.
+    /// This value is by default 1 MiB.
+    pub max_decompressed_size: usize,
+    /// Events sent to the same connection in one frame are combined into packets holding at
+    /// most this many bytes of serialized events. Larger events are sent on their own.
+    /// This value is by default 1200 bytes.
+    pub max_batch_size: usize,
+    /// Version of the application protocol, sent along with connection requests.
+    /// Requests from peers using a different version are refused.
+    /// This value is by default 0.
+    pub protocol_version: u32,
+    /// How long a connected connection may go without sending anything before a
+    /// `NetEvent::Heartbeat` is sent to keep it alive. `None` disables heartbeats.
+    /// Heartbeats are disabled by default.
+    pub heartbeat_interval: Option<Duration>,
+    /// How long a connected connection may go without receiving anything before it is
+    /// considered lost. `None` disables the timeout. Peers need to send heartbeats at a shorter
+    /// interval, or the connection times out whenever it has nothing to send.
+    /// The timeout is disabled by default.
+    pub idle_timeout: Option<Duration>,
 }
 
 impl Default for ServerConfig {
@@ -20,6 +51,14 @@ impl Default for ServerConfig {
             udp_recv_addr: "0.0.0.0:0".parse().unwrap(),
             udp_send_addr: "0.0.0.0:0".parse().unwrap(),
             max_throughput: 5000,
+            connect_retry_interval: Duration::from_secs(1),
+            connect_timeout: Duration::from_secs(10),
+            compression_threshold: Some(512),
+            max_decompressed_size: 1 << 20,
+            max_batch_size: 1200,
+            protocol_version: 0,
+            heartbeat_interval: None,
+            idle_timeout: None,
         }
     }
 }
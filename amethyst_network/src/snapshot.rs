@@ -0,0 +1,227 @@
+//! Interpolation of entity states received over the network.
+
+use std::{collections::VecDeque, marker::PhantomData, time::Duration};
+
+use amethyst_core::{
+    nalgebra::{UnitQuaternion, Vector2, Vector3},
+    specs::{Component, Join, Read, System, VecStorage, WriteStorage},
+    timing::duration_to_secs_f64,
+    Time, Transform,
+};
+
+/// A state that can be blended between two snapshots.
+pub trait Interpolate: Clone {
+    /// Returns the state `t` of the way from `self` to `other`, where `t` is in `0.0..=1.0`.
+    fn interpolate(&self, other: &Self, t: f32) -> Self;
+}
+
+impl Interpolate for f32 {
+    fn interpolate(&self, other: &Self, t: f32) -> Self {
+        self + (other - self) * t
+    }
+}
+
+impl Interpolate for Vector2<f32> {
+    fn interpolate(&self, other: &Self, t: f32) -> Self {
+        self + (other - self) * t
+    }
+}
+
+impl Interpolate for Vector3<f32> {
+    fn interpolate(&self, other: &Self, t: f32) -> Self {
+        self + (other - self) * t
+    }
+}
+
+impl Interpolate for UnitQuaternion<f32> {
+    fn interpolate(&self, other: &Self, t: f32) -> Self {
+        self.slerp(other, t)
+    }
+}
+
+impl Interpolate for Transform {
+    fn interpolate(&self, other: &Self, t: f32) -> Self {
+        let mut transform = Transform::default();
+        *transform.translation_mut() = self.translation().interpolate(other.translation(), t);
+        *transform.rotation_mut() = self.rotation().interpolate(other.rotation(), t);
+        *transform.scale_mut() = self.scale().interpolate(other.scale(), t);
+        transform
+    }
+}
+
+/// Buffer of timestamped states of a single entity, usually received from a server.
+///
+/// Push every received state together with the time it was received at, and sample the buffer
+/// a bit in the past to move smoothly between the received states. The timestamps are in seconds
+/// and should come from `Time::absolute_time_seconds` when using the
+/// `SnapshotInterpolationSystem`.
+#[derive(Clone, Debug)]
+pub struct SnapshotBuffer<T> {
+    snapshots: VecDeque<(f64, T)>,
+    capacity: usize,
+}
+
+impl<T> SnapshotBuffer<T>
+where
+    T: Interpolate,
+{
+    /// Creates a buffer which keeps at most `capacity` snapshots.
+    pub fn new(capacity: usize) -> Self {
+        SnapshotBuffer {
+            snapshots: VecDeque::with_capacity(capacity),
+            capacity: capacity.max(1),
+        }
+    }
+
+    /// Adds a snapshot of the state at `time`.
+    ///
+    /// Snapshots arriving out of order are sorted in. If the buffer is full, the oldest snapshot
+    /// is dropped.
+    pub fn push(&mut self, time: f64, state: T) {
+        let index = self
+            .snapshots
+            .iter()
+            .rposition(|&(t, _)| t <= time)
+            .map_or(0, |i| i + 1);
+        if index == 0 && self.snapshots.len() == self.capacity {
+            // Older than everything in a full buffer.
+            return;
+        }
+        self.snapshots.insert(index, (time, state));
+        if self.snapshots.len() > self.capacity {
+            self.snapshots.pop_front();
+        }
+    }
+
+    /// Returns the state at `time`, interpolated between the snapshots around it.
+    ///
+    /// Times before the first or after the last snapshot return that snapshot, `None` is only
+    /// returned if the buffer is empty.
+    pub fn sample(&self, time: f64) -> Option<T> {
+        let next = self.snapshots.iter().position(|&(t, _)| t >= time);
+        match next {
+            Some(0) => self.snapshots.front().map(|(_, state)| state.clone()),
+            Some(i) => {
+                let (from_time, ref from) = self.snapshots[i - 1];
+                let (to_time, ref to) = self.snapshots[i];
+                let t = (time - from_time) / (to_time - from_time);
+                Some(from.interpolate(to, t as f32))
+            }
+            None => self.latest().cloned(),
+        }
+    }
+
+    /// Returns the most recent snapshot.
+    pub fn latest(&self) -> Option<&T> {
+        self.snapshots.back().map(|(_, state)| state)
+    }
+
+    /// Removes all snapshots which are no longer needed to sample at `time` or later.
+    pub fn discard_before(&mut self, time: f64) {
+        while self.snapshots.len() > 1 && self.snapshots[1].0 <= time {
+            self.snapshots.pop_front();
+        }
+    }
+
+    /// Returns the number of buffered snapshots.
+    pub fn len(&self) -> usize {
+        self.snapshots.len()
+    }
+
+    /// Returns true if no snapshot has been pushed yet.
+    pub fn is_empty(&self) -> bool {
+        self.snapshots.is_empty()
+    }
+}
+
+impl<T> Default for SnapshotBuffer<T>
+where
+    T: Interpolate,
+{
+    fn default() -> Self {
+        SnapshotBuffer::new(32)
+    }
+}
+
+impl<T> Component for SnapshotBuffer<T>
+where
+    T: Send + Sync + 'static,
+{
+    type Storage = VecStorage<Self>;
+}
+
+/// Sets the `T` component of every entity with a `SnapshotBuffer<T>` to the buffered state
+/// `delay` in the past.
+///
+/// The delay should be a bit larger than the interval at which states are received, so that
+/// there usually is a newer snapshot to interpolate towards.
+pub struct SnapshotInterpolationSystem<T> {
+    delay: f64,
+    _marker: PhantomData<T>,
+}
+
+impl<T> SnapshotInterpolationSystem<T> {
+    /// Creates a new system rendering states `delay` in the past.
+    pub fn new(delay: Duration) -> Self {
+        SnapshotInterpolationSystem {
+            delay: duration_to_secs_f64(delay),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<'a, T> System<'a> for SnapshotInterpolationSystem<T>
+where
+    T: Component + Interpolate + Send + Sync,
+{
+    type SystemData = (
+        Read<'a, Time>,
+        WriteStorage<'a, SnapshotBuffer<T>>,
+        WriteStorage<'a, T>,
+    );
+
+    fn run(&mut self, (time, mut buffers, mut states): Self::SystemData) {
+        let render_time = time.absolute_time_seconds() - self.delay;
+        for (buffer, state) in (&mut buffers, &mut states).join() {
+            if let Some(sampled) = buffer.sample(render_time) {
+                *state = sampled;
+            }
+            buffer.discard_before(render_time);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SnapshotBuffer;
+
+    #[test]
+    fn samples_between_snapshots() {
+        let mut buffer = SnapshotBuffer::new(4);
+        buffer.push(1.0, 10.0f32);
+        buffer.push(3.0, 30.0);
+        // Arrives late, but is sorted in.
+        buffer.push(2.0, 20.0);
+
+        assert_eq!(buffer.sample(0.0), Some(10.0));
+        assert_eq!(buffer.sample(1.5), Some(15.0));
+        assert_eq!(buffer.sample(2.5), Some(25.0));
+        assert_eq!(buffer.sample(4.0), Some(30.0));
+    }
+
+    #[test]
+    fn keeps_newest_snapshots() {
+        let mut buffer = SnapshotBuffer::new(2);
+        buffer.push(1.0, 1.0f32);
+        buffer.push(2.0, 2.0);
+        buffer.push(3.0, 3.0);
+        buffer.push(0.5, 0.5);
+
+        assert_eq!(buffer.len(), 2);
+        assert_eq!(buffer.sample(0.0), Some(2.0));
+
+        buffer.discard_before(3.0);
+        assert_eq!(buffer.len(), 1);
+        assert_eq!(buffer.latest(), Some(&3.0));
+    }
+}
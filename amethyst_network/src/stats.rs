@@ -0,0 +1,195 @@
+//! Network traffic statistics.
+
+use std::{
+    collections::HashMap,
+    net::SocketAddr,
+    time::{Duration, Instant},
+};
+
+/// How often the per-second rates are updated.
+const RATE_WINDOW: Duration = Duration::from_secs(1);
+
+/// Traffic counters of a single connection.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ConnectionStats {
+    /// Number of events sent, including connection handshake events.
+    pub messages_sent: u64,
+    /// Number of events received, including connection handshake events.
+    pub messages_received: u64,
+    /// Number of payload bytes sent, after compression. Counted once the packets were handed to
+    /// the socket, so it can lag behind `messages_sent` by a frame.
+    pub bytes_sent: u64,
+    /// Number of payload bytes received.
+    pub bytes_received: u64,
+    /// Number of received payloads that could not be deserialized.
+    pub invalid_messages: u64,
+    /// Events sent per second, averaged over the last second.
+    pub messages_sent_per_second: u64,
+    /// Events received per second, averaged over the last second.
+    pub messages_received_per_second: u64,
+    /// Payload bytes sent per second, averaged over the last second.
+    pub bytes_sent_per_second: u64,
+    /// Payload bytes received per second, averaged over the last second.
+    pub bytes_received_per_second: u64,
+}
+
+/// Resource holding the traffic counters of all connections handled by the `NetSocketSystem`,
+/// keyed by the `target_receiver` address of each `NetConnection`.
+///
+/// The counters are never reset, the per-second rates are updated once a second. The counters of
+/// a connection are removed together with its `NetConnection`.
+///
+/// RTT, packet loss and resend counts are not available: every event is sent as an unreliable
+/// packet, so there are no acknowledgements to measure them from.
+#[derive(Debug, Default)]
+pub struct NetworkStats {
+    connections: HashMap<SocketAddr, ConnectionStats>,
+    /// The counters at the start of the current rate window.
+    window: HashMap<SocketAddr, ConnectionStats>,
+    window_start: Option<Instant>,
+}
+
+impl NetworkStats {
+    /// Returns the counters of the connection with the given address.
+    pub fn get(&self, addr: &SocketAddr) -> Option<&ConnectionStats> {
+        self.connections.get(addr)
+    }
+
+    /// Iterates over the counters of all connections.
+    pub fn iter(&self) -> impl Iterator<Item = (&SocketAddr, &ConnectionStats)> {
+        self.connections.iter()
+    }
+
+    /// Returns the sum of the counters of all connections.
+    pub fn total(&self) -> ConnectionStats {
+        self.connections
+            .values()
+            .fold(ConnectionStats::default(), |mut total, stats| {
+                total.messages_sent += stats.messages_sent;
+                total.messages_received += stats.messages_received;
+                total.bytes_sent += stats.bytes_sent;
+                total.bytes_received += stats.bytes_received;
+                total.invalid_messages += stats.invalid_messages;
+                total.messages_sent_per_second += stats.messages_sent_per_second;
+                total.messages_received_per_second += stats.messages_received_per_second;
+                total.bytes_sent_per_second += stats.bytes_sent_per_second;
+                total.bytes_received_per_second += stats.bytes_received_per_second;
+                total
+            })
+    }
+
+    pub(crate) fn connection_mut(&mut self, addr: SocketAddr) -> &mut ConnectionStats {
+        self.connections.entry(addr).or_default()
+    }
+
+    /// Returns the counters of a connection which is already tracked, so late byte counts of a
+    /// removed connection do not bring it back.
+    pub(crate) fn existing_connection_mut(
+        &mut self,
+        addr: &SocketAddr,
+    ) -> Option<&mut ConnectionStats> {
+        self.connections.get_mut(addr)
+    }
+
+    /// Removes the counters of the connections for which `live` returns false.
+    pub(crate) fn retain(&mut self, mut live: impl FnMut(&SocketAddr) -> bool) {
+        self.connections.retain(|addr, _| live(addr));
+        let connections = &self.connections;
+        self.window.retain(|addr, _| connections.contains_key(addr));
+    }
+
+    /// Updates the per-second rates if the current window is over.
+    pub(crate) fn update_rates(&mut self, now: Instant) {
+        let window_start = *self.window_start.get_or_insert(now);
+        let elapsed = now.duration_since(window_start);
+        if elapsed < RATE_WINDOW {
+            return;
+        }
+        let seconds = elapsed.as_secs() as f64 + f64::from(elapsed.subsec_nanos()) * 1e-9;
+        let per_second = |current: u64, start: u64| ((current - start) as f64 / seconds) as u64;
+
+        for (addr, stats) in &mut self.connections {
+            let start = self.window.entry(*addr).or_default();
+            stats.messages_sent_per_second = per_second(stats.messages_sent, start.messages_sent);
+            stats.messages_received_per_second =
+                per_second(stats.messages_received, start.messages_received);
+            stats.bytes_sent_per_second = per_second(stats.bytes_sent, start.bytes_sent);
+            stats.bytes_received_per_second =
+                per_second(stats.bytes_received, start.bytes_received);
+            *start = stats.clone();
+        }
+        self.window_start = Some(now);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        net::SocketAddr,
+        time::{Duration, Instant},
+    };
+
+    use super::{ConnectionStats, NetworkStats};
+
+    #[test]
+    fn total_sums_all_connections() {
+        let a: SocketAddr = "127.0.0.1:21300".parse().unwrap();
+        let b: SocketAddr = "127.0.0.1:21301".parse().unwrap();
+
+        let mut stats = NetworkStats::default();
+        stats.connection_mut(a).bytes_sent += 100;
+        stats.connection_mut(a).messages_sent += 2;
+        stats.connection_mut(b).bytes_sent += 50;
+        stats.connection_mut(b).bytes_received += 10;
+
+        assert_eq!(stats.get(&a).unwrap().bytes_sent, 100);
+        assert_eq!(
+            stats.total(),
+            ConnectionStats {
+                messages_sent: 2,
+                messages_received: 0,
+                bytes_sent: 150,
+                bytes_received: 10,
+                invalid_messages: 0,
+                ..ConnectionStats::default()
+            }
+        );
+    }
+
+    #[test]
+    fn rates_are_updated_once_a_window_is_over() {
+        let a: SocketAddr = "127.0.0.1:21302".parse().unwrap();
+        let start = Instant::now();
+
+        let mut stats = NetworkStats::default();
+        stats.connection_mut(a).bytes_sent += 100;
+        stats.update_rates(start);
+        stats.connection_mut(a).bytes_sent += 400;
+        stats.connection_mut(a).messages_received += 6;
+
+        stats.update_rates(start + Duration::from_millis(500));
+        assert_eq!(stats.get(&a).unwrap().bytes_sent_per_second, 0);
+
+        stats.update_rates(start + Duration::from_secs(2));
+        let connection = stats.get(&a).unwrap();
+        assert_eq!(connection.bytes_sent_per_second, 250);
+        assert_eq!(connection.messages_received_per_second, 3);
+        assert_eq!(connection.messages_sent_per_second, 0);
+    }
+
+    #[test]
+    fn retain_removes_closed_connections() {
+        let a: SocketAddr = "127.0.0.1:21303".parse().unwrap();
+        let b: SocketAddr = "127.0.0.1:21304".parse().unwrap();
+
+        let mut stats = NetworkStats::default();
+        stats.connection_mut(a).messages_sent += 1;
+        stats.connection_mut(b).messages_sent += 1;
+        stats.retain(|addr| *addr == a);
+
+        assert!(stats.get(&a).is_some());
+        assert!(stats.get(&b).is_none());
+        assert!(stats.existing_connection_mut(&b).is_none());
+        assert_eq!(stats.iter().count(), 1);
+    }
+}
@@ -1,6 +1,11 @@
 #[cfg(test)]
 mod test {
-    use std::{net::SocketAddr, thread::sleep, time::Duration};
+    use std::{
+        net::SocketAddr,
+        sync::atomic::{AtomicBool, Ordering},
+        thread::sleep,
+        time::Duration,
+    };
 
     use amethyst_core::{
         shred::{Dispatcher, DispatcherBuilder, SystemData},
@@ -46,9 +51,15 @@ mod test {
         let storage = world_sv.read_storage::<NetConnection<()>>();
         let comp = storage.get(conn_to_client_entity).unwrap();
 
-        assert_eq!(comp.receive_buffer.read(&mut rcv).next(), Some(&test_event));
+        let mut events = comp.receive_buffer.read(&mut rcv);
+        // The accepted connection request comes first.
+        match events.next() {
+            Some(NetEvent::Connect { .. }) => {}
+            other => panic!("Expected a `Connect` event, got {:?}", other),
+        }
+        assert_eq!(events.next(), Some(&test_event));
         // We should have consumed the only event in the iterator by calling next().
-        assert!(comp.receive_buffer.read(&mut rcv).count() == 0);
+        assert!(events.next().is_none());
     }
 
     #[test]
@@ -99,7 +110,532 @@ mod test {
 
         let storage = world_sv.read_storage::<NetConnection<()>>();
         let comp = storage.get(conn_to_client_entity).unwrap();
-        assert_eq!(comp.receive_buffer.read(&mut rcv).count(), 100);
+        assert_eq!(
+            comp.receive_buffer
+                .read(&mut rcv)
+                .filter(|event| **event == test_event)
+                .count(),
+            100
+        );
+    }
+
+    #[test]
+    fn handshake_connects_both_sides() {
+        // server got one socket receiving and one sending
+        let server_send: SocketAddr = "127.0.0.1:21208".parse().unwrap();
+        let server_receive: SocketAddr = "127.0.0.1:21209".parse().unwrap();
+
+        // client got one socket receiving and one sending
+        let client_send: SocketAddr = "127.0.0.1:21210".parse().unwrap();
+        let client_receive: SocketAddr = "127.0.0.1:21211".parse().unwrap();
+
+        let (mut world_cl, mut cl_dispatch, mut world_sv, mut sv_dispatch) = build(
+            server_send.clone(),
+            server_receive.clone(),
+            client_send.clone(),
+            client_receive.clone(),
+        );
+
+        let conn_to_server_entity = world_cl
+            .create_entity()
+            .with(NetConnection::<()>::new(server_receive, server_send))
+            .build();
+        let mut conn_to_client = NetConnection::<()>::new(client_receive, client_send);
+        let mut sv_rcv = conn_to_client.receive_buffer.register_reader();
+        let conn_to_client_entity = world_sv.create_entity().with(conn_to_client).build();
+
+        // Client sends `Connect`, the server accepts it and answers with `Connected`.
+        cl_dispatch.dispatch(&mut world_cl.res);
+        sleep(Duration::from_millis(500));
+        sv_dispatch.dispatch(&mut world_sv.res);
+        sleep(Duration::from_millis(500));
+        cl_dispatch.dispatch(&mut world_cl.res);
+
+        let state = |world: &World, entity| {
+            world
+                .read_storage::<NetConnection<()>>()
+                .get(entity)
+                .unwrap()
+                .state
+                .clone()
+        };
+        assert_eq!(
+            state(&world_sv, conn_to_client_entity),
+            ConnectionState::Connected
+        );
+        assert_eq!(
+            state(&world_cl, conn_to_server_entity),
+            ConnectionState::Connected
+        );
+
+        let client_uuid = world_cl.read_resource::<NetIdentity>().uuid;
+        let sv_storage = world_sv.read_storage::<NetConnection<()>>();
+        let sv_events: Vec<_> = sv_storage
+            .get(conn_to_client_entity)
+            .unwrap()
+            .receive_buffer
+            .read(&mut sv_rcv)
+            .cloned()
+            .collect();
+        assert_eq!(
+            sv_events,
+            vec![NetEvent::Connect {
+                client_uuid,
+                token: Vec::new(),
+                protocol_version: 0,
+            }]
+        );
+    }
+
+    #[test]
+    fn repeated_connect_is_not_validated_again() {
+        let server_send: SocketAddr = "127.0.0.1:21226".parse().unwrap();
+        let server_receive: SocketAddr = "127.0.0.1:21227".parse().unwrap();
+        let client_send: SocketAddr = "127.0.0.1:21228".parse().unwrap();
+        let client_receive: SocketAddr = "127.0.0.1:21229".parse().unwrap();
+
+        // The token can only be used once.
+        let used = AtomicBool::new(false);
+        let (mut world_cl, mut cl_dispatch, mut world_sv, mut sv_dispatch) = build_with(
+            server_send.clone(),
+            server_receive.clone(),
+            client_send.clone(),
+            client_receive.clone(),
+            0,
+            Some(Box::new(move |_, _, _: &[u8]| {
+                if used.swap(true, Ordering::SeqCst) {
+                    Err("Token already used".to_owned())
+                } else {
+                    Ok(())
+                }
+            })),
+        );
+
+        let mut conn_to_server = NetConnection::<()>::new(server_receive, server_send);
+        let mut cl_rcv = conn_to_server.receive_buffer.register_reader();
+        let conn_to_server_entity = world_cl.create_entity().with(conn_to_server).build();
+        let mut conn_to_client = NetConnection::<()>::new(client_receive, client_send);
+        let mut sv_rcv = conn_to_client.receive_buffer.register_reader();
+        let conn_to_client_entity = world_sv.create_entity().with(conn_to_client).build();
+
+        // The client sends its request again before reading the answer to the first one.
+        cl_dispatch.dispatch(&mut world_cl.res);
+        sleep(Duration::from_millis(500));
+        sv_dispatch.dispatch(&mut world_sv.res);
+        sleep(Duration::from_millis(600));
+        cl_dispatch.dispatch(&mut world_cl.res);
+        sleep(Duration::from_millis(500));
+        sv_dispatch.dispatch(&mut world_sv.res);
+
+        let sv_storage = world_sv.read_storage::<NetConnection<()>>();
+        let sv_comp = sv_storage.get(conn_to_client_entity).unwrap();
+        assert_eq!(sv_comp.state, ConnectionState::Connected);
+        let sv_events: Vec<_> = sv_comp.receive_buffer.read(&mut sv_rcv).collect();
+        assert_eq!(sv_events.len(), 1);
+        match sv_events[0] {
+            NetEvent::Connect { .. } => {}
+            other => panic!("Expected a `Connect` event, got {:?}", other),
+        }
+
+        // The client only learns about the new connection once.
+        let cl_storage = world_cl.read_storage::<NetConnection<()>>();
+        let cl_comp = cl_storage.get(conn_to_server_entity).unwrap();
+        assert_eq!(cl_comp.state, ConnectionState::Connected);
+        assert_eq!(cl_comp.receive_buffer.read(&mut cl_rcv).count(), 1);
+    }
+
+    #[test]
+    fn refused_peer_events_are_dropped() {
+        let server_send: SocketAddr = "127.0.0.1:21218".parse().unwrap();
+        let server_receive: SocketAddr = "127.0.0.1:21219".parse().unwrap();
+        let client_send: SocketAddr = "127.0.0.1:21220".parse().unwrap();
+        let client_receive: SocketAddr = "127.0.0.1:21221".parse().unwrap();
+
+        let (mut world_cl, mut cl_dispatch, mut world_sv, mut sv_dispatch) = build_with(
+            server_send.clone(),
+            server_receive.clone(),
+            client_send.clone(),
+            client_receive.clone(),
+            0,
+            Some(Box::new(|_, _, token: &[u8]| {
+                if token == b"secret" {
+                    Ok(())
+                } else {
+                    Err("Wrong token".to_owned())
+                }
+            })),
+        );
+
+        let mut conn_to_server = NetConnection::<()>::new(server_receive, server_send)
+            .with_connect_token(b"guess".to_vec());
+        conn_to_server
+            .send_buffer
+            .single_write(NetEvent::Custom(()));
+        let conn_to_server_entity = world_cl.create_entity().with(conn_to_server).build();
+
+        let mut conn_to_client = NetConnection::<()>::new(client_receive, client_send);
+        let mut rcv = conn_to_client.receive_buffer.register_reader();
+        let conn_to_client_entity = world_sv.create_entity().with(conn_to_client).build();
+
+        // The client keeps sending events after its request was refused.
+        for _ in 0..2 {
+            cl_dispatch.dispatch(&mut world_cl.res);
+            sleep(Duration::from_millis(500));
+            sv_dispatch.dispatch(&mut world_sv.res);
+            sleep(Duration::from_millis(500));
+            world_cl
+                .write_storage::<NetConnection<()>>()
+                .get_mut(conn_to_server_entity)
+                .unwrap()
+                .send_buffer
+                .single_write(NetEvent::Custom(()));
+        }
+        cl_dispatch.dispatch(&mut world_cl.res);
+
+        let storage = world_sv.read_storage::<NetConnection<()>>();
+        let comp = storage.get(conn_to_client_entity).unwrap();
+        assert_eq!(comp.state, ConnectionState::Disconnected);
+        assert!(comp
+            .receive_buffer
+            .read(&mut rcv)
+            .all(|event| event.custom().is_none()));
+        assert_eq!(
+            world_cl
+                .read_storage::<NetConnection<()>>()
+                .get(conn_to_server_entity)
+                .unwrap()
+                .state,
+            ConnectionState::Disconnected
+        );
+    }
+
+    #[test]
+    fn version_mismatch_disconnects_both_sides() {
+        let server_send: SocketAddr = "127.0.0.1:21222".parse().unwrap();
+        let server_receive: SocketAddr = "127.0.0.1:21223".parse().unwrap();
+        let client_send: SocketAddr = "127.0.0.1:21224".parse().unwrap();
+        let client_receive: SocketAddr = "127.0.0.1:21225".parse().unwrap();
+
+        let (mut world_cl, mut cl_dispatch, mut world_sv, mut sv_dispatch) = build_with(
+            server_send.clone(),
+            server_receive.clone(),
+            client_send.clone(),
+            client_receive.clone(),
+            2,
+            None,
+        );
+
+        let mut conn_to_server = NetConnection::<()>::new(server_receive, server_send);
+        let mut cl_rcv = conn_to_server.receive_buffer.register_reader();
+        conn_to_server
+            .send_buffer
+            .single_write(NetEvent::Custom(()));
+        let conn_to_server_entity = world_cl.create_entity().with(conn_to_server).build();
+
+        let mut conn_to_client = NetConnection::<()>::new(client_receive, client_send);
+        let mut sv_rcv = conn_to_client.receive_buffer.register_reader();
+        let conn_to_client_entity = world_sv.create_entity().with(conn_to_client).build();
+
+        cl_dispatch.dispatch(&mut world_cl.res);
+        sleep(Duration::from_millis(500));
+        sv_dispatch.dispatch(&mut world_sv.res);
+        sleep(Duration::from_millis(500));
+        cl_dispatch.dispatch(&mut world_cl.res);
+
+        let sv_storage = world_sv.read_storage::<NetConnection<()>>();
+        let sv_comp = sv_storage.get(conn_to_client_entity).unwrap();
+        assert_eq!(sv_comp.state, ConnectionState::Disconnected);
+        let sv_events: Vec<_> = sv_comp.receive_buffer.read(&mut sv_rcv).cloned().collect();
+        assert_eq!(
+            sv_events,
+            vec![NetEvent::VersionMismatch { theirs: 0, ours: 2 }]
+        );
+
+        let cl_storage = world_cl.read_storage::<NetConnection<()>>();
+        let cl_comp = cl_storage.get(conn_to_server_entity).unwrap();
+        assert_eq!(cl_comp.state, ConnectionState::Disconnected);
+        assert!(cl_comp
+            .receive_buffer
+            .read(&mut cl_rcv)
+            .any(|event| *event == NetEvent::VersionMismatch { theirs: 2, ours: 0 }));
+    }
+
+    #[test]
+    fn disconnected_connection_is_flushed_once() {
+        let server_send: SocketAddr = "127.0.0.1:21214".parse().unwrap();
+        let server_receive: SocketAddr = "127.0.0.1:21215".parse().unwrap();
+        let client_send: SocketAddr = "127.0.0.1:21216".parse().unwrap();
+        let client_receive: SocketAddr = "127.0.0.1:21217".parse().unwrap();
+
+        let (mut world_cl, mut cl_dispatch, _world_sv, _sv_dispatch) = build(
+            server_send.clone(),
+            server_receive.clone(),
+            client_send.clone(),
+            client_receive.clone(),
+        );
+
+        let mut conn_to_server = NetConnection::<()>::new(server_receive, server_send);
+        conn_to_server.disconnect("Leaving");
+        let conn_to_server_entity = world_cl.create_entity().with(conn_to_server).build();
+
+        cl_dispatch.dispatch(&mut world_cl.res);
+        world_cl
+            .write_storage::<NetConnection<()>>()
+            .get_mut(conn_to_server_entity)
+            .unwrap()
+            .send_buffer
+            .single_write(NetEvent::TextMessage {
+                msg: "Too late".to_string(),
+            });
+        cl_dispatch.dispatch(&mut world_cl.res);
+
+        let stats = world_cl.read_resource::<NetworkStats>();
+        assert_eq!(stats.get(&server_receive).unwrap().messages_sent, 1);
+    }
+
+    #[test]
+    fn unanswered_connection_times_out() {
+        let server_send: SocketAddr = "127.0.0.1:21230".parse().unwrap();
+        let server_receive: SocketAddr = "127.0.0.1:21231".parse().unwrap();
+        let client_send: SocketAddr = "127.0.0.1:21232".parse().unwrap();
+        let client_receive: SocketAddr = "127.0.0.1:21233".parse().unwrap();
+
+        let client_config = ServerConfig {
+            connect_timeout: Duration::from_millis(200),
+            ..config(client_send, client_receive)
+        };
+        // The server never runs, so the request is never answered.
+        let (mut world_cl, mut cl_dispatch, _world_sv, _sv_dispatch) =
+            build_with_configs(client_config, config(server_send, server_receive), None);
+
+        let mut conn_to_server = NetConnection::<()>::new(server_receive, server_send);
+        let mut rcv = conn_to_server.receive_buffer.register_reader();
+        let conn_to_server_entity = world_cl.create_entity().with(conn_to_server).build();
+
+        cl_dispatch.dispatch(&mut world_cl.res);
+        sleep(Duration::from_millis(300));
+        cl_dispatch.dispatch(&mut world_cl.res);
+
+        let storage = world_cl.read_storage::<NetConnection<()>>();
+        let comp = storage.get(conn_to_server_entity).unwrap();
+        assert_eq!(comp.state, ConnectionState::TimedOut);
+        let events: Vec<_> = comp.receive_buffer.read(&mut rcv).cloned().collect();
+        assert_eq!(
+            events,
+            vec![NetEvent::Disconnected {
+                reason: "Connection timed out".to_owned(),
+            }]
+        );
+    }
+
+    #[test]
+    fn heartbeats_keep_connection_alive_until_idle_timeout() {
+        let server_send: SocketAddr = "127.0.0.1:21234".parse().unwrap();
+        let server_receive: SocketAddr = "127.0.0.1:21235".parse().unwrap();
+        let client_send: SocketAddr = "127.0.0.1:21236".parse().unwrap();
+        let client_receive: SocketAddr = "127.0.0.1:21237".parse().unwrap();
+
+        let client_config = ServerConfig {
+            heartbeat_interval: Some(Duration::from_millis(100)),
+            idle_timeout: None,
+            ..config(client_send, client_receive)
+        };
+        let server_config = ServerConfig {
+            idle_timeout: Some(Duration::from_secs(1)),
+            ..config(server_send, server_receive)
+        };
+        let (mut world_cl, mut cl_dispatch, mut world_sv, mut sv_dispatch) =
+            build_with_configs(client_config, server_config, None);
+
+        world_cl
+            .create_entity()
+            .with(NetConnection::<()>::new(server_receive, server_send))
+            .build();
+        let mut conn_to_client = NetConnection::<()>::new(client_receive, client_send);
+        let mut rcv = conn_to_client.receive_buffer.register_reader();
+        let conn_to_client_entity = world_sv.create_entity().with(conn_to_client).build();
+
+        // Handshake, then the client stays connected without sending any events itself, for
+        // longer than the idle timeout of the server.
+        cl_dispatch.dispatch(&mut world_cl.res);
+        sleep(Duration::from_millis(50));
+        sv_dispatch.dispatch(&mut world_sv.res);
+        for _ in 0..4 {
+            sleep(Duration::from_millis(200));
+            cl_dispatch.dispatch(&mut world_cl.res);
+            sleep(Duration::from_millis(50));
+            sv_dispatch.dispatch(&mut world_sv.res);
+        }
+
+        let state = |world: &World| {
+            world
+                .read_storage::<NetConnection<()>>()
+                .get(conn_to_client_entity)
+                .unwrap()
+                .state
+                .clone()
+        };
+        assert_eq!(state(&world_sv), ConnectionState::Connected);
+        assert!(
+            world_cl
+                .read_resource::<NetworkStats>()
+                .get(&server_receive)
+                .unwrap()
+                .messages_sent
+                > 2
+        );
+
+        // The client goes silent.
+        sleep(Duration::from_millis(1200));
+        sv_dispatch.dispatch(&mut world_sv.res);
+
+        assert_eq!(state(&world_sv), ConnectionState::TimedOut);
+        let storage = world_sv.read_storage::<NetConnection<()>>();
+        let events: Vec<_> = storage
+            .get(conn_to_client_entity)
+            .unwrap()
+            .receive_buffer
+            .read(&mut rcv)
+            .cloned()
+            .collect();
+        // Heartbeats are not passed on.
+        assert_eq!(events.len(), 2);
+        match events[0] {
+            NetEvent::Connect { .. } => {}
+            ref other => panic!("Expected a `Connect` event, got {:?}", other),
+        }
+        assert_eq!(
+            events[1],
+            NetEvent::Disconnected {
+                reason: "Connection went silent".to_owned(),
+            }
+        );
+    }
+
+    #[test]
+    fn payload_compression_roundtrip() {
+        let event = NetEvent::<()>::TextMessage {
+            msg: "a".repeat(1000),
+        };
+        let data = bincode::serialize(&[event.clone()]).unwrap();
+
+        let compressed = crate::encode_payload(data.clone(), Some(512));
+        assert!(compressed.len() < data.len());
+        assert_eq!(
+            deserialize_events::<()>(&compressed, 1 << 20).unwrap(),
+            vec![event.clone()]
+        );
+
+        let uncompressed = crate::encode_payload(data.clone(), None);
+        assert_eq!(uncompressed.len(), data.len() + 1);
+        assert_eq!(
+            deserialize_events::<()>(&uncompressed, 1 << 20).unwrap(),
+            vec![event]
+        );
+    }
+
+    #[test]
+    fn oversized_compressed_payload_is_rejected() {
+        let data = bincode::serialize(&[NetEvent::<()>::TextMessage {
+            msg: "a".repeat(1000),
+        }])
+        .unwrap();
+        let compressed = crate::encode_payload(data.clone(), Some(512));
+
+        match deserialize_events::<()>(&compressed, data.len() - 1) {
+            Err(crate::error::Error::PayloadTooLarge(size)) => assert_eq!(size, data.len()),
+            other => panic!("Expected the payload to be rejected, got {:?}", other),
+        }
+
+        // A forged header declaring a huge size is rejected before allocating anything.
+        let mut forged = vec![1];
+        forged.extend_from_slice(&u32::max_value().to_le_bytes());
+        forged.extend_from_slice(&[0; 8]);
+        match deserialize_events::<()>(&forged, 1 << 20) {
+            Err(crate::error::Error::PayloadTooLarge(_)) => {}
+            other => panic!("Expected the payload to be rejected, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn events_are_batched() {
+        let (sender, receiver) = std::sync::mpsc::sync_channel(10);
+        let addr: SocketAddr = "127.0.0.1:21212".parse().unwrap();
+        let message = |len| NetEvent::<()>::TextMessage {
+            msg: "a".repeat(len),
+        };
+        let events = vec![message(100), message(100), message(1000), message(10)];
+
+        let bytes_sent = send_events(&events, addr, &sender, None, 1200);
+
+        let packets: Vec<_> = receiver
+            .try_iter()
+            .map(|event| match event {
+                ServerSocketEvent::Packet(packet) => packet,
+                _ => panic!("Expected a packet"),
+            })
+            .collect();
+        let batches: Vec<_> = packets
+            .iter()
+            .map(|packet| deserialize_events::<()>(packet.payload(), 1 << 20).unwrap())
+            .collect();
+        assert_eq!(batches, vec![events[..2].to_vec(), events[2..].to_vec()]);
+        assert_eq!(
+            bytes_sent,
+            packets
+                .iter()
+                .map(|packet| packet.payload().len() as u64)
+                .sum::<u64>()
+        );
+    }
+
+    #[test]
+    fn unserializable_events_are_skipped() {
+        struct Unserializable;
+
+        impl serde::Serialize for Unserializable {
+            fn serialize<S: serde::Serializer>(
+                &self,
+                _: S,
+            ) -> std::result::Result<S::Ok, S::Error> {
+                Err(serde::ser::Error::custom("not serializable"))
+            }
+        }
+
+        let (sender, receiver) = std::sync::mpsc::sync_channel(10);
+        let addr: SocketAddr = "127.0.0.1:21213".parse().unwrap();
+        let events = vec![
+            NetEvent::TextMessage {
+                msg: "before".to_string(),
+            },
+            NetEvent::Custom(Unserializable),
+            NetEvent::TextMessage {
+                msg: "after".to_string(),
+            },
+        ];
+
+        send_events(&events, addr, &sender, None, 1200);
+
+        let received: Vec<_> = receiver
+            .try_iter()
+            .flat_map(|event| match event {
+                ServerSocketEvent::Packet(packet) => {
+                    deserialize_events::<()>(packet.payload(), 1 << 20).unwrap()
+                }
+                _ => panic!("Expected a packet"),
+            })
+            .collect();
+        assert_eq!(
+            received,
+            vec![
+                NetEvent::TextMessage {
+                    msg: "before".to_string(),
+                },
+                NetEvent::TextMessage {
+                    msg: "after".to_string(),
+                },
+            ]
+        );
     }
 
     fn build<'a, 'b>(
@@ -108,22 +644,42 @@ mod test {
         client_send: SocketAddr,
         client_receive: SocketAddr,
     ) -> (World, Dispatcher<'a, 'b>, World, Dispatcher<'a, 'b>) {
-        let mut world_cl = World::new();
-        let mut world_sv = World::new();
-
-        // client config
-        let client_config = ServerConfig {
-            udp_send_addr: client_send,
-            udp_recv_addr: client_receive,
-            max_throughput: 10000,
-        };
+        build_with(
+            server_send,
+            server_receive,
+            client_send,
+            client_receive,
+            0,
+            None,
+        )
+    }
 
-        // server config
+    fn build_with<'a, 'b>(
+        server_send: SocketAddr,
+        server_receive: SocketAddr,
+        client_send: SocketAddr,
+        client_receive: SocketAddr,
+        server_protocol_version: u32,
+        server_validator: Option<ConnectionValidator>,
+    ) -> (World, Dispatcher<'a, 'b>, World, Dispatcher<'a, 'b>) {
         let server_config = ServerConfig {
-            udp_send_addr: server_send,
-            udp_recv_addr: server_receive,
-            max_throughput: 10000,
+            protocol_version: server_protocol_version,
+            ..config(server_send, server_receive)
         };
+        build_with_configs(
+            config(client_send, client_receive),
+            server_config,
+            server_validator,
+        )
+    }
+
+    fn build_with_configs<'a, 'b>(
+        client_config: ServerConfig,
+        server_config: ServerConfig,
+        server_validator: Option<ConnectionValidator>,
+    ) -> (World, Dispatcher<'a, 'b>, World, Dispatcher<'a, 'b>) {
+        let mut world_cl = World::new();
+        let mut world_sv = World::new();
 
         let mut cl_dispatch = DispatcherBuilder::new()
             .with(
@@ -133,15 +689,31 @@ mod test {
             )
             .build();
         cl_dispatch.setup(&mut world_cl.res);
+        let mut server_system = NetSocketSystem::<()>::new(server_config, Vec::new()).unwrap();
+        if let Some(validator) = server_validator {
+            server_system = server_system.with_connection_validator(validator);
+        }
         let mut sv_dispatch = DispatcherBuilder::new()
-            .with(
-                NetSocketSystem::<()>::new(server_config, Vec::new()).unwrap(),
-                "s",
-                &[],
-            )
+            .with(server_system, "s", &[])
             .build();
         sv_dispatch.setup(&mut world_sv.res);
 
         (world_cl, cl_dispatch, world_sv, sv_dispatch)
     }
+
+    fn config(send: SocketAddr, receive: SocketAddr) -> ServerConfig {
+        ServerConfig {
+            udp_send_addr: send,
+            udp_recv_addr: receive,
+            max_throughput: 10000,
+            connect_retry_interval: Duration::from_secs(1),
+            connect_timeout: Duration::from_secs(10),
+            compression_threshold: Some(512),
+            max_decompressed_size: 1 << 20,
+            max_batch_size: 1200,
+            protocol_version: 0,
+            heartbeat_interval: Some(Duration::from_secs(1)),
+            idle_timeout: Some(Duration::from_secs(10)),
+        }
+    }
 }
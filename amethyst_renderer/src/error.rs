@@ -23,6 +23,10 @@ pub(crate) enum Error {
     DecodeImageError,
     /// Failed to create texture.
     CreateTextureError,
+    /// Unsupported or malformed DDS file.
+    UnsupportedDds(String),
+    /// Malformed MagicaVoxel file.
+    InvalidVox(String),
 }
 
 impl error::Error for Error {}
@@ -48,6 +52,8 @@ impl fmt::Display for Error {
             ),
             DecodeImageError => write!(fmt, "Image decoding failed"),
             CreateTextureError => write!(fmt, "Failed to create texture from texture data"),
+            UnsupportedDds(ref e) => write!(fmt, "Unsupported DDS file: {}", e),
+            InvalidVox(ref e) => write!(fmt, "Invalid VOX file: {}", e),
         }
     }
 }
@@ -0,0 +1,544 @@
+//! Loading of DirectDraw Surface (`.dds`) textures.
+//!
+//! The renderer can't sample block compressed surfaces directly, so BCn data is decompressed
+//! into RGBA8 while importing.
+
+use gfx::format::ChannelType;
+use image::RgbaImage;
+use serde::{Deserialize, Serialize};
+
+use amethyst_assets::SimpleFormat;
+use amethyst_error::Error;
+
+use crate::{
+    error,
+    formats::{texture::premultiply_alpha, ImageData, TextureData, TextureMetadata},
+    tex::Texture,
+};
+
+const MAGIC: &[u8; 4] = b"DDS ";
+const HEADER_SIZE: usize = 124;
+const DX10_HEADER_SIZE: usize = 20;
+
+const DDPF_FOURCC: u32 = 0x4;
+const DDPF_RGB: u32 = 0x40;
+const DDPF_LUMINANCE: u32 = 0x2_0000;
+const DDPF_ALPHAPIXELS: u32 = 0x1;
+
+const DDSCAPS2_CUBEMAP: u32 = 0x200;
+const DDSCAPS2_VOLUME: u32 = 0x20_0000;
+
+const DX10_MISC_TEXTURECUBE: u32 = 0x4;
+
+/// Allows loading of DDS files.
+///
+/// Supported surface encodings are BC1 - BC5 (`DXT1`, `DXT3`, `DXT5`, `ATI1`, `ATI2` and their
+/// `DX10` equivalents) as well as uncompressed RGB(A), BGR(A) and luminance data.
+/// If the file contains a mip chain, all of its levels are loaded and used as the mipmaps of the
+/// texture instead of generating them.
+///
+/// Cubemaps, texture arrays and volume textures are rejected, because `Texture` only supports
+/// single 2D surfaces.
+///
+/// If the `DX10` header marks the surface as sRGB, the channel type of the passed
+/// `TextureMetadata` is overridden with `ChannelType::Srgb`. With
+/// `TextureMetadata::premultiply_alpha`, every level is premultiplied after decoding.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct DdsFormat;
+
+impl DdsFormat {
+    /// Loads a DDS image from a byte slice.
+    pub fn from_data(data: &[u8], options: TextureMetadata) -> Result<TextureData, Error> {
+        let surface = DdsSurface::parse(data)?;
+        let options = if surface.srgb {
+            options.with_channel(ChannelType::Srgb)
+        } else {
+            options
+        };
+        let mut levels = surface.decode()?;
+        if options.premultiply_alpha {
+            levels.iter_mut().for_each(premultiply_alpha);
+        }
+        let mut levels = levels.into_iter();
+        let rgba = levels.next().expect("DDS surface without levels");
+        let mipmaps = levels.collect();
+        Ok(TextureData::Image(ImageData { rgba, mipmaps }, options))
+    }
+}
+
+impl SimpleFormat<Texture> for DdsFormat {
+    fn name() -> &'static str {
+        "DDS"
+    }
+
+    type Options = TextureMetadata;
+
+    fn import(&self, bytes: Vec<u8>, options: TextureMetadata) -> Result<TextureData, Error> {
+        DdsFormat::from_data(&bytes, options)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Encoding {
+    Bc1,
+    Bc2,
+    Bc3,
+    Bc4,
+    Bc5,
+    Uncompressed {
+        bytes_per_pixel: usize,
+        masks: [u32; 4],
+        luminance: bool,
+    },
+}
+
+struct DdsSurface<'a> {
+    width: u32,
+    height: u32,
+    levels: u32,
+    encoding: Encoding,
+    srgb: bool,
+    data: &'a [u8],
+}
+
+fn unsupported<T>(reason: &str) -> Result<T, Error> {
+    Err(Error::from(error::Error::UnsupportedDds(reason.to_owned())))
+}
+
+fn read_u32(data: &[u8], offset: usize) -> u32 {
+    u32::from(data[offset])
+        | u32::from(data[offset + 1]) << 8
+        | u32::from(data[offset + 2]) << 16
+        | u32::from(data[offset + 3]) << 24
+}
+
+impl<'a> DdsSurface<'a> {
+    fn parse(data: &'a [u8]) -> Result<Self, Error> {
+        if data.len() < MAGIC.len() + HEADER_SIZE || &data[..4] != MAGIC {
+            return unsupported("missing DDS header");
+        }
+        if read_u32(data, 4) as usize != HEADER_SIZE {
+            return unsupported("invalid header size");
+        }
+
+        let height = read_u32(data, 12);
+        let width = read_u32(data, 16);
+        let mip_count = read_u32(data, 28);
+        let pf_flags = read_u32(data, 80);
+        let four_cc = &data[84..88];
+        let bit_count = read_u32(data, 88);
+        let caps2 = read_u32(data, 112);
+
+        if caps2 & DDSCAPS2_CUBEMAP != 0 {
+            return unsupported("cubemaps are not supported");
+        }
+        if caps2 & DDSCAPS2_VOLUME != 0 {
+            return unsupported("volume textures are not supported");
+        }
+
+        let mut offset = MAGIC.len() + HEADER_SIZE;
+        let mut srgb = false;
+        let encoding = if pf_flags & DDPF_FOURCC != 0 {
+            match four_cc {
+                b"DXT1" => Encoding::Bc1,
+                b"DXT2" | b"DXT3" => Encoding::Bc2,
+                b"DXT4" | b"DXT5" => Encoding::Bc3,
+                b"ATI1" | b"BC4U" => Encoding::Bc4,
+                b"ATI2" | b"BC5U" => Encoding::Bc5,
+                b"DX10" => {
+                    if data.len() < offset + DX10_HEADER_SIZE {
+                        return unsupported("truncated DX10 header");
+                    }
+                    let dxgi_format = read_u32(data, offset);
+                    let misc_flag = read_u32(data, offset + 8);
+                    let array_size = read_u32(data, offset + 12);
+                    offset += DX10_HEADER_SIZE;
+                    if misc_flag & DX10_MISC_TEXTURECUBE != 0 {
+                        return unsupported("cubemaps are not supported");
+                    }
+                    if array_size > 1 {
+                        return unsupported("texture arrays are not supported");
+                    }
+                    let (encoding, is_srgb) = dxgi_encoding(dxgi_format)?;
+                    srgb = is_srgb;
+                    encoding
+                }
+                _ => return unsupported("unknown FourCC code"),
+            }
+        } else if pf_flags & (DDPF_RGB | DDPF_LUMINANCE) != 0 {
+            if bit_count == 0 || bit_count > 32 || bit_count % 8 != 0 {
+                return unsupported("unsupported bit count");
+            }
+            let alpha_mask = if pf_flags & DDPF_ALPHAPIXELS != 0 {
+                read_u32(data, 104)
+            } else {
+                0
+            };
+            Encoding::Uncompressed {
+                bytes_per_pixel: (bit_count / 8) as usize,
+                masks: [
+                    read_u32(data, 92),
+                    read_u32(data, 96),
+                    read_u32(data, 100),
+                    alpha_mask,
+                ],
+                luminance: pf_flags & DDPF_LUMINANCE != 0,
+            }
+        } else {
+            return unsupported("unknown pixel format");
+        };
+
+        // Files without a mip chain may store 0 as mip count. Levels below 1x1 don't exist.
+        let max_levels = 32 - width.max(height).leading_zeros();
+        let surface = DdsSurface {
+            width,
+            height,
+            levels: mip_count.min(max_levels).max(1),
+            encoding,
+            srgb,
+            data: &data[offset..],
+        };
+        let size = (0..surface.levels).try_fold(0usize, |size, level| {
+            let (w, h) = surface.level_dimensions(level);
+            surface.level_size(w, h)?.checked_add(size)
+        });
+        match size {
+            Some(size) if surface.data.len() >= size => Ok(surface),
+            Some(_) => unsupported("surface data is truncated"),
+            None => unsupported("surface dimensions are too large"),
+        }
+    }
+
+    fn level_dimensions(&self, level: u32) -> (u32, u32) {
+        ((self.width >> level).max(1), (self.height >> level).max(1))
+    }
+
+    /// Returns the number of bytes a level is stored in, or `None` if it doesn't fit in `usize`.
+    fn level_size(&self, width: u32, height: u32) -> Option<usize> {
+        let (w, h) = (width as usize, height as usize);
+        let blocks = ((w + 3) / 4).checked_mul((h + 3) / 4)?;
+        match self.encoding {
+            Encoding::Bc1 | Encoding::Bc4 => blocks.checked_mul(8),
+            Encoding::Bc2 | Encoding::Bc3 | Encoding::Bc5 => blocks.checked_mul(16),
+            Encoding::Uncompressed {
+                bytes_per_pixel, ..
+            } => w.checked_mul(h)?.checked_mul(bytes_per_pixel),
+        }
+    }
+
+    /// Decodes all levels of the mip chain, starting with the top level.
+    fn decode(&self) -> Result<Vec<RgbaImage>, Error> {
+        let mut offset = 0;
+        (0..self.levels)
+            .map(|level| {
+                let (w, h) = self.level_dimensions(level);
+                let start = offset;
+                let data = self
+                    .level_size(w, h)
+                    .and_then(|size| start.checked_add(size))
+                    .and_then(|end| {
+                        offset = end;
+                        self.data.get(start..end)
+                    });
+                match data {
+                    Some(data) => self.decode_level(w, h, data),
+                    None => unsupported("surface data is truncated"),
+                }
+            })
+            .collect()
+    }
+
+    fn decode_level(&self, width: u32, height: u32, data: &[u8]) -> Result<RgbaImage, Error> {
+        let (w, h) = (width as usize, height as usize);
+        let mut out = match w.checked_mul(h).and_then(|texels| texels.checked_mul(4)) {
+            Some(size) => vec![0u8; size],
+            None => return unsupported("surface dimensions are too large"),
+        };
+        match self.encoding {
+            Encoding::Uncompressed {
+                bytes_per_pixel,
+                masks,
+                luminance,
+            } => {
+                for (pixel, src) in out.chunks_mut(4).zip(data.chunks(bytes_per_pixel)) {
+                    let value = src
+                        .iter()
+                        .rev()
+                        .fold(0u32, |acc, byte| acc << 8 | u32::from(*byte));
+                    let r = extract_channel(value, masks[0], 0);
+                    pixel[0] = r;
+                    if luminance {
+                        pixel[1] = r;
+                        pixel[2] = r;
+                    } else {
+                        pixel[1] = extract_channel(value, masks[1], 0);
+                        pixel[2] = extract_channel(value, masks[2], 0);
+                    }
+                    pixel[3] = extract_channel(value, masks[3], 255);
+                }
+            }
+            encoding => {
+                let block_size = match encoding {
+                    Encoding::Bc1 | Encoding::Bc4 => 8,
+                    _ => 16,
+                };
+                let blocks_x = (w + 3) / 4;
+                for (index, block) in data
+                    .chunks(block_size)
+                    .take(blocks_x * ((h + 3) / 4))
+                    .enumerate()
+                {
+                    let texels = decode_block(encoding, block);
+                    let (bx, by) = ((index % blocks_x) * 4, (index / blocks_x) * 4);
+                    for (i, texel) in texels.iter().enumerate() {
+                        let (x, y) = (bx + i % 4, by + i / 4);
+                        if x < w && y < h {
+                            let start = (y * w + x) * 4;
+                            out[start..start + 4].copy_from_slice(texel);
+                        }
+                    }
+                }
+            }
+        }
+
+        match RgbaImage::from_raw(width, height, out) {
+            Some(image) => Ok(image),
+            None => unsupported("decoded surface does not match its dimensions"),
+        }
+    }
+}
+
+fn dxgi_encoding(format: u32) -> Result<(Encoding, bool), Error> {
+    let rgba = Encoding::Uncompressed {
+        bytes_per_pixel: 4,
+        masks: [0x0000_00ff, 0x0000_ff00, 0x00ff_0000, 0xff00_0000],
+        luminance: false,
+    };
+    let bgra = Encoding::Uncompressed {
+        bytes_per_pixel: 4,
+        masks: [0x00ff_0000, 0x0000_ff00, 0x0000_00ff, 0xff00_0000],
+        luminance: false,
+    };
+    Ok(match format {
+        28 => (rgba, false),
+        29 => (rgba, true),
+        71 => (Encoding::Bc1, false),
+        72 => (Encoding::Bc1, true),
+        74 => (Encoding::Bc2, false),
+        75 => (Encoding::Bc2, true),
+        77 => (Encoding::Bc3, false),
+        78 => (Encoding::Bc3, true),
+        80 => (Encoding::Bc4, false),
+        83 => (Encoding::Bc5, false),
+        87 => (bgra, false),
+        91 => (bgra, true),
+        _ => return unsupported("unsupported DXGI format"),
+    })
+}
+
+/// Extracts a channel described by `mask` and scales it to 8 bits.
+fn extract_channel(value: u32, mask: u32, default: u8) -> u8 {
+    if mask == 0 {
+        return default;
+    }
+    let bits = mask.count_ones();
+    let raw = u64::from((value & mask) >> mask.trailing_zeros());
+    let max = (1u64 << bits) - 1;
+    (raw * 255 / max) as u8
+}
+
+/// Decodes a single 4x4 block into its texels in row-major order.
+fn decode_block(encoding: Encoding, block: &[u8]) -> [[u8; 4]; 16] {
+    let mut texels = [[0, 0, 0, 255]; 16];
+    match encoding {
+        Encoding::Bc1 => decode_color_block(block, true, &mut texels),
+        Encoding::Bc2 => {
+            decode_color_block(&block[8..], false, &mut texels);
+            for (i, texel) in texels.iter_mut().enumerate() {
+                let nibble = (block[i / 2] >> ((i % 2) * 4)) & 0x0f;
+                texel[3] = nibble * 17;
+            }
+        }
+        Encoding::Bc3 => {
+            decode_color_block(&block[8..], false, &mut texels);
+            decode_alpha_block(block, 3, &mut texels);
+        }
+        Encoding::Bc4 => decode_alpha_block(block, 0, &mut texels),
+        Encoding::Bc5 => {
+            decode_alpha_block(block, 0, &mut texels);
+            decode_alpha_block(&block[8..], 1, &mut texels);
+        }
+        Encoding::Uncompressed { .. } => unreachable!(),
+    }
+    texels
+}
+
+fn expand_565(color: u16) -> [u8; 3] {
+    let r = u32::from((color >> 11) & 0x1f);
+    let g = u32::from((color >> 5) & 0x3f);
+    let b = u32::from(color & 0x1f);
+    [
+        (r * 255 / 31) as u8,
+        (g * 255 / 63) as u8,
+        (b * 255 / 31) as u8,
+    ]
+}
+
+fn lerp_channels(a: [u8; 3], b: [u8; 3], wa: u32, wb: u32) -> [u8; 3] {
+    let lerp = |x: u8, y: u8| ((u32::from(x) * wa + u32::from(y) * wb) / (wa + wb)) as u8;
+    [lerp(a[0], b[0]), lerp(a[1], b[1]), lerp(a[2], b[2])]
+}
+
+fn decode_color_block(block: &[u8], allow_transparent: bool, texels: &mut [[u8; 4]; 16]) {
+    let c0 = u16::from(block[0]) | u16::from(block[1]) << 8;
+    let c1 = u16::from(block[2]) | u16::from(block[3]) << 8;
+    let (e0, e1) = (expand_565(c0), expand_565(c1));
+
+    let mut palette = [[0, 0, 0, 255]; 4];
+    let mut set = |i: usize, rgb: [u8; 3], a: u8| {
+        palette[i] = [rgb[0], rgb[1], rgb[2], a];
+    };
+    set(0, e0, 255);
+    set(1, e1, 255);
+    if c0 > c1 || !allow_transparent {
+        set(2, lerp_channels(e0, e1, 2, 1), 255);
+        set(3, lerp_channels(e0, e1, 1, 2), 255);
+    } else {
+        set(2, lerp_channels(e0, e1, 1, 1), 255);
+        set(3, [0, 0, 0], 0);
+    }
+
+    let indices = read_u32(block, 4);
+    for (i, texel) in texels.iter_mut().enumerate() {
+        let color = palette[((indices >> (i * 2)) & 0x3) as usize];
+        texel[0] = color[0];
+        texel[1] = color[1];
+        texel[2] = color[2];
+        texel[3] = color[3];
+    }
+}
+
+fn decode_alpha_block(block: &[u8], channel: usize, texels: &mut [[u8; 4]; 16]) {
+    let (a0, a1) = (u32::from(block[0]), u32::from(block[1]));
+    // Interpolate 6 values if the endpoints are ordered, otherwise 4 values
+    // followed by explicit `0` and `255` entries.
+    let steps = if a0 > a1 { 7 } else { 5 };
+    let mut palette = [0u8; 8];
+    for (i, value) in palette.iter_mut().enumerate() {
+        let i = i as u32;
+        *value = match i {
+            0 => a0,
+            1 => a1,
+            _ if i < steps + 1 => ((steps + 1 - i) * a0 + (i - 1) * a1) / steps,
+            6 => 0,
+            _ => 255,
+        } as u8;
+    }
+
+    let indices = block[2..8]
+        .iter()
+        .rev()
+        .fold(0u64, |acc, byte| acc << 8 | u64::from(*byte));
+    for (i, texel) in texels.iter_mut().enumerate() {
+        texel[channel] = palette[((indices >> (i * 3)) & 0x7) as usize];
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decode_block, DdsSurface, Encoding};
+
+    fn header(pf_flags: u32, four_cc: &[u8; 4], bit_count: u32, masks: [u32; 4]) -> Vec<u8> {
+        let mut data = vec![0u8; 128];
+        let mut write = |offset: usize, value: u32| {
+            data[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+        };
+        write(4, 124);
+        write(12, 2);
+        write(16, 2);
+        write(80, pf_flags);
+        write(88, bit_count);
+        for (i, mask) in masks.iter().enumerate() {
+            write(92 + i * 4, *mask);
+        }
+        data[..4].copy_from_slice(b"DDS ");
+        data[84..88].copy_from_slice(four_cc);
+        data
+    }
+
+    #[test]
+    fn decodes_uncompressed_bgra() {
+        let mut data = header(
+            0x41,
+            &[0; 4],
+            32,
+            [0x00ff_0000, 0x0000_ff00, 0x0000_00ff, 0xff00_0000],
+        );
+        data.extend_from_slice(&[1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16]);
+
+        let mut levels = DdsSurface::parse(&data).unwrap().decode().unwrap();
+        assert_eq!(levels.len(), 1);
+        assert_eq!(
+            levels.remove(0).into_raw(),
+            vec![3, 2, 1, 4, 7, 6, 5, 8, 11, 10, 9, 12, 15, 14, 13, 16]
+        );
+    }
+
+    #[test]
+    fn decodes_mip_chain() {
+        let mut data = header(
+            0x41,
+            &[0; 4],
+            32,
+            [0x00ff_0000, 0x0000_ff00, 0x0000_00ff, 0xff00_0000],
+        );
+        // Claims more levels than a 2x2 surface can have.
+        data[28..32].copy_from_slice(&5u32.to_le_bytes());
+        data.extend_from_slice(&[0; 16]);
+        assert!(DdsSurface::parse(&data).is_err());
+
+        data.extend_from_slice(&[1, 2, 3, 4]);
+        let levels = DdsSurface::parse(&data).unwrap().decode().unwrap();
+        assert_eq!(levels.len(), 2);
+        assert_eq!(levels[0].dimensions(), (2, 2));
+        assert_eq!(levels[1].clone().into_raw(), vec![3, 2, 1, 4]);
+    }
+
+    #[test]
+    fn rejects_truncated_surface() {
+        let data = header(0x4, b"DXT1", 0, [0; 4]);
+        assert!(DdsSurface::parse(&data).is_err());
+    }
+
+    #[test]
+    fn rejects_oversized_surface() {
+        let mut data = header(
+            0x41,
+            &[0; 4],
+            32,
+            [0x00ff_0000, 0x0000_ff00, 0x0000_00ff, 0xff00_0000],
+        );
+        // The size of the top level overflows `usize` instead of being allocated.
+        data[12..16].copy_from_slice(&u32::max_value().to_le_bytes());
+        data[16..20].copy_from_slice(&u32::max_value().to_le_bytes());
+        data.extend_from_slice(&[0; 16]);
+        assert!(DdsSurface::parse(&data).is_err());
+
+        let mut data = header(0x4, b"DXT1", 0, [0; 4]);
+        data[12..16].copy_from_slice(&u32::max_value().to_le_bytes());
+        data[16..20].copy_from_slice(&u32::max_value().to_le_bytes());
+        data[28..32].copy_from_slice(&32u32.to_le_bytes());
+        data.extend_from_slice(&[0; 8]);
+        assert!(DdsSurface::parse(&data).is_err());
+    }
+
+    #[test]
+    fn decodes_bc1_endpoints() {
+        // Pure red and pure blue endpoints, first row uses indices 0 and 1, the rest index 0.
+        let block = [0x00, 0xf8, 0x1f, 0x00, 0x04, 0x00, 0x00, 0x00];
+        let texels = decode_block(Encoding::Bc1, &block);
+        assert_eq!(texels[0], [255, 0, 0, 255]);
+        assert_eq!(texels[1], [0, 0, 255, 255]);
+        assert_eq!(texels[15], [255, 0, 0, 255]);
+    }
+}
@@ -0,0 +1,322 @@
+use std::{collections::HashMap, fmt, ops::RangeInclusive};
+
+use serde::{
+    de::{Deserializer, MapAccess, SeqAccess, Visitor},
+    Deserialize, Serialize,
+};
+
+use amethyst_assets::{Handle, SimpleFormat};
+use amethyst_error::{format_err, Error, ResultExt};
+
+use crate::{error, Sprite, SpriteSheet, Texture};
+
+/// Allows loading of sprite sheets from the JSON data files exported by TexturePacker and by
+/// Aseprite (`--data` / "Export Sprite Sheet"), which share the same layout.
+///
+/// Both the hash and the array variant of the `frames` field are supported. Sprites are created
+/// in the order the frames appear in the file.
+///
+/// Aseprite frame tags are imported into `SpriteSheet::ranges`, mapping each tag name to the
+/// sprite numbers it covers. The playback direction of a tag is not imported.
+///
+/// Trimmed frames and TexturePacker pivot points are turned into sprite offsets, so the pivot
+/// (or the center of the untrimmed frame) is placed at the position of the entity. Frames that
+/// were rotated by the packer are not supported.
+///
+/// The texture is loaded separately and passed as option, just like for `SpriteSheetFormat`.
+/// ```rust,no_run
+/// # use amethyst_assets::{Loader, AssetStorage};
+/// # use amethyst_renderer::{JsonSpriteSheetFormat, SpriteSheet, Texture, PngFormat, TextureMetadata};
+/// #
+/// # fn load_sprite_sheet() {
+/// #   let world = amethyst_core::specs::World::new(); // Normally, you would use Amethyst's world
+/// #   let loader = world.read_resource::<Loader>();
+/// #   let spritesheet_storage = world.read_resource::<AssetStorage<SpriteSheet>>();
+/// #   let texture_storage = world.read_resource::<AssetStorage<Texture>>();
+/// let texture_handle = loader.load(
+///     "characters.png",
+///     PngFormat,
+///     TextureMetadata::srgb_scale(),
+///     (),
+///     &texture_storage,
+/// );
+/// let spritesheet_handle = loader.load(
+///     "characters.json",
+///     JsonSpriteSheetFormat,
+///     texture_handle,
+///     (),
+///     &spritesheet_storage,
+/// );
+/// # }
+/// ```
+#[derive(Clone, Deserialize, Serialize)]
+pub struct JsonSpriteSheetFormat;
+
+impl SimpleFormat<SpriteSheet> for JsonSpriteSheetFormat {
+    fn name() -> &'static str {
+        "JSON_SPRITE_SHEET"
+    }
+
+    type Options = Handle<Texture>;
+
+    fn import(&self, bytes: Vec<u8>, texture: Self::Options) -> Result<SpriteSheet, Error> {
+        let sheet: JsonSpriteSheet =
+            serde_json::from_slice(&bytes).with_context(|_| error::Error::LoadSpritesheetError)?;
+        let (sprites, ranges) = sprites_and_ranges(sheet)?;
+        Ok(SpriteSheet {
+            texture,
+            sprites,
+            ranges,
+        })
+    }
+}
+
+fn sprites_and_ranges(
+    sheet: JsonSpriteSheet,
+) -> Result<(Vec<Sprite>, HashMap<String, RangeInclusive<usize>>), Error> {
+    let (sheet_w, sheet_h) = (sheet.meta.size.w, sheet.meta.size.h);
+
+    let mut sprites: Vec<Sprite> = Vec::with_capacity(sheet.frames.len());
+    for frame in sheet.frames {
+        if frame.rotated {
+            return Err(format_err!("Rotated frames are not supported"))
+                .with_context(|_| error::Error::LoadSpritesheetError);
+        }
+        let rect = &frame.frame;
+        let (trim_x, trim_y) = frame
+            .sprite_source_size
+            .as_ref()
+            .map_or((0, 0), |trim| (trim.x, trim.y));
+        let (source_w, source_h) = frame
+            .source_size
+            .as_ref()
+            .map_or((rect.w, rect.h), |size| (size.w, size.h));
+        let (pivot_x, pivot_y) = frame.pivot.as_ref().map_or((0.5, 0.5), |p| (p.x, p.y));
+
+        // Distance from the center of the packed frame to the pivot, with the Y axis
+        // pointing up.
+        let offsets = [
+            pivot_x * source_w as f32 - (trim_x as f32 + rect.w as f32 / 2.0),
+            (trim_y as f32 + rect.h as f32 / 2.0) - pivot_y * source_h as f32,
+        ];
+        sprites.push(Sprite::from_pixel_values(
+            sheet_w, sheet_h, rect.w, rect.h, rect.x, rect.y, offsets,
+        ));
+    }
+
+    let mut ranges = HashMap::with_capacity(sheet.meta.frame_tags.len());
+    for tag in sheet.meta.frame_tags {
+        if tag.from > tag.to || tag.to >= sprites.len() {
+            return Err(format_err!(
+                "Frame tag {:?} covers frames {} to {}, but there are only {} frames",
+                tag.name,
+                tag.from,
+                tag.to,
+                sprites.len()
+            ))
+            .with_context(|_| error::Error::LoadSpritesheetError);
+        }
+        ranges.insert(tag.name, tag.from..=tag.to);
+    }
+    Ok((sprites, ranges))
+}
+
+#[derive(Deserialize)]
+struct JsonSpriteSheet {
+    #[serde(deserialize_with = "deserialize_frames")]
+    frames: Vec<JsonFrame>,
+    meta: JsonMeta,
+}
+
+#[derive(Deserialize)]
+struct JsonFrame {
+    frame: JsonRect,
+    #[serde(default)]
+    rotated: bool,
+    #[serde(rename = "spriteSourceSize")]
+    sprite_source_size: Option<JsonRect>,
+    #[serde(rename = "sourceSize")]
+    source_size: Option<JsonSize>,
+    pivot: Option<JsonPoint>,
+}
+
+#[derive(Deserialize)]
+struct JsonMeta {
+    size: JsonSize,
+    #[serde(rename = "frameTags", default)]
+    frame_tags: Vec<JsonFrameTag>,
+}
+
+#[derive(Deserialize)]
+struct JsonFrameTag {
+    name: String,
+    from: usize,
+    to: usize,
+}
+
+#[derive(Deserialize)]
+struct JsonRect {
+    x: u32,
+    y: u32,
+    w: u32,
+    h: u32,
+}
+
+#[derive(Deserialize)]
+struct JsonSize {
+    w: u32,
+    h: u32,
+}
+
+#[derive(Deserialize)]
+struct JsonPoint {
+    x: f32,
+    y: f32,
+}
+
+/// Reads `frames` either as an array or as a map from frame names to frames, keeping the order of
+/// the file in both cases.
+fn deserialize_frames<'de, D>(deserializer: D) -> Result<Vec<JsonFrame>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    struct FramesVisitor;
+
+    impl<'de> Visitor<'de> for FramesVisitor {
+        type Value = Vec<JsonFrame>;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+            formatter.write_str("a list or a map of frames")
+        }
+
+        fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+        where
+            A: SeqAccess<'de>,
+        {
+            let mut frames = Vec::new();
+            while let Some(frame) = seq.next_element()? {
+                frames.push(frame);
+            }
+            Ok(frames)
+        }
+
+        fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+        where
+            A: MapAccess<'de>,
+        {
+            let mut frames = Vec::new();
+            while let Some((_, frame)) = map.next_entry::<String, JsonFrame>()? {
+                frames.push(frame);
+            }
+            Ok(frames)
+        }
+    }
+
+    deserializer.deserialize_any(FramesVisitor)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{sprites_and_ranges, JsonSpriteSheet};
+
+    #[test]
+    fn parses_hash_and_array_frames_in_file_order() {
+        let hash = br#"{
+            "frames": {
+                "b.png": { "frame": { "x": 0, "y": 0, "w": 16, "h": 16 } },
+                "a.png": { "frame": { "x": 16, "y": 0, "w": 16, "h": 16 }, "rotated": false }
+            },
+            "meta": { "size": { "w": 32, "h": 16 } }
+        }"#;
+        let array = br#"{
+            "frames": [
+                { "filename": "b.png", "frame": { "x": 0, "y": 0, "w": 16, "h": 16 } },
+                { "filename": "a.png", "frame": { "x": 16, "y": 0, "w": 16, "h": 16 } }
+            ],
+            "meta": { "size": { "w": 32, "h": 16 } }
+        }"#;
+
+        for bytes in &[&hash[..], &array[..]] {
+            let sheet: JsonSpriteSheet = serde_json::from_slice(bytes).unwrap();
+            let xs: Vec<u32> = sheet.frames.iter().map(|f| f.frame.x).collect();
+            assert_eq!(xs, vec![0, 16]);
+            assert_eq!(sheet.meta.size.w, 32);
+            assert!(sheet.meta.frame_tags.is_empty());
+        }
+    }
+
+    #[test]
+    fn parses_aseprite_frame_tags() {
+        let bytes = br#"{
+            "frames": [
+                { "frame": { "x": 0, "y": 0, "w": 16, "h": 16 } },
+                { "frame": { "x": 16, "y": 0, "w": 16, "h": 16 } }
+            ],
+            "meta": {
+                "size": { "w": 32, "h": 16 },
+                "frameTags": [
+                    { "name": "walk", "from": 0, "to": 1, "direction": "forward" }
+                ]
+            }
+        }"#;
+
+        let sheet: JsonSpriteSheet = serde_json::from_slice(bytes).unwrap();
+        let tag = &sheet.meta.frame_tags[0];
+        assert_eq!((tag.name.as_str(), tag.from, tag.to), ("walk", 0, 1));
+    }
+
+    #[test]
+    fn offsets_place_pivot_at_entity() {
+        let bytes = br#"{
+            "frames": [
+                {
+                    "frame": { "x": 0, "y": 0, "w": 16, "h": 8 },
+                    "trimmed": true,
+                    "spriteSourceSize": { "x": 4, "y": 20, "w": 16, "h": 8 },
+                    "sourceSize": { "w": 32, "h": 32 }
+                },
+                {
+                    "frame": { "x": 16, "y": 0, "w": 16, "h": 16 },
+                    "pivot": { "x": 0.5, "y": 1.0 }
+                },
+                {
+                    "frame": { "x": 32, "y": 0, "w": 16, "h": 8 },
+                    "spriteSourceSize": { "x": 0, "y": 8, "w": 16, "h": 8 },
+                    "sourceSize": { "w": 16, "h": 16 },
+                    "pivot": { "x": 0.25, "y": 0.75 }
+                }
+            ],
+            "meta": {
+                "size": { "w": 48, "h": 16 },
+                "frameTags": [{ "name": "idle", "from": 1, "to": 2 }]
+            }
+        }"#;
+
+        let sheet: JsonSpriteSheet = serde_json::from_slice(bytes).unwrap();
+        let (sprites, ranges) = sprites_and_ranges(sheet).unwrap();
+        // Trimmed frame: the content is 4 pixels left of and 8 pixels below the center of the
+        // untrimmed frame.
+        assert_eq!(sprites[0].offsets, [4., 8.]);
+        assert_eq!((sprites[0].width, sprites[0].height), (16., 8.));
+        // Bottom center pivot: the sprite is drawn above the entity.
+        assert_eq!(sprites[1].offsets, [0., -8.]);
+        // Trimmed frame with a pivot.
+        assert_eq!(sprites[2].offsets, [-4., 0.]);
+        assert_eq!(sprites[2].tex_coords.left, 32. / 48.);
+        assert_eq!(ranges["idle"], 1..=2);
+    }
+
+    #[test]
+    fn rejects_frame_tags_outside_the_frames() {
+        let bytes = br#"{
+            "frames": [{ "frame": { "x": 0, "y": 0, "w": 16, "h": 16 } }],
+            "meta": {
+                "size": { "w": 16, "h": 16 },
+                "frameTags": [{ "name": "walk", "from": 0, "to": 1 }]
+            }
+        }"#;
+
+        let sheet: JsonSpriteSheet = serde_json::from_slice(bytes).unwrap();
+        assert!(sprites_and_ranges(sheet).is_err());
+    }
+}
@@ -14,7 +14,7 @@ use wavefront_obj::obj::{
 };
 
 use crate::{
-    mesh::{Mesh, MeshBuilder, MeshHandle},
+    mesh::{Mesh, MeshBounds, MeshBuilder, MeshHandle},
     vertex::*,
     Renderer,
 };
@@ -200,11 +200,36 @@ fn from_data(obj_set: ObjSet) -> Vec<PosNormTex> {
     result
 }
 
+/// Computes the bounding volumes of the mesh described by `data`.
+fn mesh_bounds(data: &MeshData) -> MeshBounds {
+    match *data {
+        MeshData::PosColor(ref vertices) => {
+            MeshBounds::from_positions(vertices.iter().map(|v| v.position))
+        }
+        MeshData::PosColorNorm(ref vertices) => {
+            MeshBounds::from_positions(vertices.iter().map(|v| v.position))
+        }
+        MeshData::PosTex(ref vertices) => {
+            MeshBounds::from_positions(vertices.iter().map(|v| v.position))
+        }
+        MeshData::PosNormTex(ref vertices) => {
+            MeshBounds::from_positions(vertices.iter().map(|v| v.position))
+        }
+        MeshData::PosNormTangTex(ref vertices) => {
+            MeshBounds::from_positions(vertices.iter().map(|v| v.position))
+        }
+        MeshData::Creator(ref creator) => {
+            MeshBounds::from_positions(creator.vertices().iter().map(|v| Vector3::from(v.0)))
+        }
+    }
+}
+
 /// Create mesh
 pub fn create_mesh_asset(
     data: MeshData,
     renderer: &mut Renderer,
 ) -> Result<ProcessingState<Mesh>, Error> {
+    let bounds = mesh_bounds(&data);
     let data = match data {
         MeshData::PosColor(ref vertices) => {
             let mb = MeshBuilder::new(vertices);
@@ -229,8 +254,11 @@ pub fn create_mesh_asset(
         MeshData::Creator(creator) => creator.build(renderer),
     };
 
-    data.map(ProcessingState::Loaded)
-        .with_context(|_| format_err!("Failed to build mesh"))
+    data.map(|mut mesh| {
+        mesh.set_bounds(bounds);
+        ProcessingState::Loaded(mesh)
+    })
+    .with_context(|_| format_err!("Failed to build mesh"))
 }
 
 /// Build Mesh with vertex buffer combination
@@ -1,7 +1,10 @@
 //! Provides texture formats
 //!
 
-pub use self::{mesh::*, mtl::*, sprite::*, texture::*};
+pub use self::{dds::*, mesh::*, mtl::*, sprite::*, texture::*, vox::*};
+
+#[cfg(feature = "json")]
+pub use self::json_sprite::JsonSpriteSheetFormat;
 
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 
@@ -11,10 +14,14 @@ use amethyst_error::Error;
 
 use crate::{shape::InternalShape, Mesh, ShapePrefab, Texture};
 
+mod dds;
+#[cfg(feature = "json")]
+mod json_sprite;
 mod mesh;
 mod mtl;
 mod sprite;
 mod texture;
+mod vox;
 
 /// Internal mesh loading
 ///
@@ -120,7 +120,7 @@ impl SimpleFormat<SpriteSheet> for SpriteSheetFormat {
             );
             sprites.push(sprite);
         }
-        Ok(SpriteSheet { texture, sprites })
+        Ok(SpriteSheet::new(texture, sprites))
     }
 }
 
@@ -168,7 +168,7 @@ impl<'a> PrefabData<'a> for SpriteRenderPrefab {
 
         let texture = self.texture.add_to_entity(entity, tex_data, entities)?;
 
-        let sheet = SpriteSheet { texture, sprites };
+        let sheet = SpriteSheet::new(texture, sprites);
         let sheet_handle = loader.load_from_data(sheet, (), sheet_storage);
 
         let render = SpriteRender {
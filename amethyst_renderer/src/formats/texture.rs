@@ -15,7 +15,8 @@ use amethyst_error::{Error, ResultExt};
 
 use crate::{
     error,
-    tex::{FilterMethod, Texture, TextureBuilder},
+    formats::DdsFormat,
+    tex::{FilterMethod, Texture, TextureBuilder, WrapMode},
     types::SurfaceFormat,
     Renderer,
 };
@@ -29,8 +30,15 @@ pub struct TextureMetadata {
     #[serde(default = "serde_helper::default_sampler")]
     pub sampler: SamplerInfo,
     /// Mipmapping levels. The default is one level.
+    ///
+    /// For image data (`TextureData::Image`), the additional levels are generated from the
+    /// decoded image when the texture is created.
     #[serde(default = "serde_helper::default_mip_levels")]
     pub mip_levels: u8,
+    /// Multiply the color channels of decoded images, including all levels of a DDS mip chain,
+    /// with their alpha channel. The default is `false`.
+    #[serde(default)]
+    pub premultiply_alpha: bool,
     /// Dynamic texture
     #[serde(default)]
     pub dynamic: bool,
@@ -63,6 +71,7 @@ impl TextureMetadata {
         TextureMetadata {
             sampler: serde_helper::default_sampler(),
             mip_levels: serde_helper::default_mip_levels(),
+            premultiply_alpha: false,
             dynamic: false,
             format: SurfaceFormat::get_surface_type(),
             size: None,
@@ -105,12 +114,30 @@ impl TextureMetadata {
         self
     }
 
+    /// Sets the wrap mode of the sampler for all texture coordinates.
+    pub fn with_wrap_mode(mut self, wrap_mode: WrapMode) -> Self {
+        self.sampler.wrap_mode = (wrap_mode, wrap_mode, wrap_mode);
+        self
+    }
+
+    /// Sets the level of detail bias of the sampler.
+    pub fn with_lod_bias(mut self, bias: f32) -> Self {
+        self.sampler.lod_bias = bias.into();
+        self
+    }
+
     /// Mipmapping
     pub fn with_mip_levels(mut self, mip_levels: u8) -> Self {
         self.mip_levels = mip_levels;
         self
     }
 
+    /// Premultiplied alpha
+    pub fn with_premultiplied_alpha(mut self, premultiply: bool) -> Self {
+        self.premultiply_alpha = premultiply;
+        self
+    }
+
     /// Surface type
     pub fn with_format(mut self, format: SurfaceType) -> Self {
         self.format = format;
@@ -315,6 +342,9 @@ impl<'de> serde::Deserialize<'de> for ImageData {
 pub struct ImageData {
     /// The raw image data.
     pub rgba: RgbaImage,
+    /// The mipmap levels following the base level, if the format provides them. If this is
+    /// empty, mipmaps are generated according to `TextureMetadata::mip_levels`.
+    pub mipmaps: Vec<RgbaImage>,
 }
 
 fn load_into_rgba8_from_memory(
@@ -333,11 +363,46 @@ fn load_into_rgba8_from_memory(
                 }
             }
         })
-        .map(|rgba| TextureData::Image(ImageData { rgba }, options))
+        .map(|mut rgba| {
+            if options.premultiply_alpha {
+                premultiply_alpha(&mut rgba);
+            }
+            TextureData::Image(
+                ImageData {
+                    rgba,
+                    mipmaps: Vec::new(),
+                },
+                options,
+            )
+        })
         // TODO: Add more context? File path or containing gltf archive?
         .with_context(|_| error::Error::DecodeImageError)
 }
 
+pub(super) fn premultiply_alpha(image: &mut RgbaImage) {
+    for pixel in image.pixels_mut() {
+        let alpha = u16::from(pixel[3]);
+        for channel in &mut pixel.data[..3] {
+            *channel = ((u16::from(*channel) * alpha + 127) / 255) as u8;
+        }
+    }
+}
+
+/// Generates the mipmap levels following the base level of `image`, up to a total of `levels`
+/// levels (including the base level).
+fn generate_mipmaps(image: &RgbaImage, levels: u8) -> Vec<Vec<u8>> {
+    use image::imageops::{resize, FilterType};
+
+    let (w, h) = image.dimensions();
+    (1..u32::from(levels))
+        .take_while(|level| (w >> level) > 0 || (h >> level) > 0)
+        .map(|level| {
+            let (w, h) = ((w >> level).max(1), (h >> level).max(1));
+            resize(image, w, h, FilterType::Triangle).into_raw()
+        })
+        .collect()
+}
+
 /// Allows loading of jpg or jpeg files.
 #[derive(Clone, Deserialize, Serialize)]
 pub struct JpgFormat;
@@ -425,6 +490,54 @@ impl SimpleFormat<Texture> for TgaFormat {
     }
 }
 
+/// Allows loading of Radiance HDR (`.hdr`) files.
+///
+/// The decoded image is stored as 32 bit float RGBA texels (`SurfaceType::R32_G32_B32_A32`
+/// with `ChannelType::Float`), which overrides the format, channel type and size of the passed
+/// `TextureMetadata`. Use this for environment maps and other textures that need values
+/// outside of `[0, 1]`.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct HdrFormat;
+
+impl HdrFormat {
+    /// Loads a Radiance HDR image from a byte slice.
+    pub fn from_data(data: &[u8], options: TextureMetadata) -> Result<TextureData, Error> {
+        use image::hdr::HDRDecoder;
+
+        let decoder = HDRDecoder::new(data).with_context(|_| error::Error::DecodeImageError)?;
+        let metadata = decoder.metadata();
+        let (w, h) = (metadata.width, metadata.height);
+        if w > u32::from(u16::max_value()) || h > u32::from(u16::max_value()) {
+            return Err(Error::from(error::Error::UnsupportedTextureSize(w, h)));
+        }
+        let rgb = decoder
+            .read_image_hdr()
+            .with_context(|_| error::Error::DecodeImageError)?;
+        let mut texels = Vec::with_capacity(rgb.len() * 4);
+        for texel in rgb {
+            let [r, g, b] = texel.data;
+            texels.extend_from_slice(&[r, g, b, 1.0]);
+        }
+        let options = options
+            .with_format(SurfaceType::R32_G32_B32_A32)
+            .with_channel(ChannelType::Float)
+            .with_size(w as u16, h as u16);
+        Ok(TextureData::F32(texels, options))
+    }
+}
+
+impl SimpleFormat<Texture> for HdrFormat {
+    fn name() -> &'static str {
+        "HDR"
+    }
+
+    type Options = TextureMetadata;
+
+    fn import(&self, bytes: Vec<u8>, options: TextureMetadata) -> Result<TextureData, Error> {
+        HdrFormat::from_data(&bytes, options)
+    }
+}
+
 /// Create a texture asset.
 pub fn create_texture_asset(
     data: TextureData,
@@ -513,19 +626,25 @@ fn create_texture_asset_from_image(
 ) -> Result<Texture, Error> {
     let fmt = SurfaceType::R8_G8_B8_A8;
     let chan = options.channel;
-    let rgba = image.rgba;
+    let ImageData { rgba, mipmaps } = image;
     let w = rgba.width();
     let h = rgba.height();
     if w > u32::from(u16::max_value()) || h > u32::from(u16::max_value()) {
         return Err(Error::from(error::Error::UnsupportedTextureSize(w, h)));
     }
+    let mipmaps = if mipmaps.is_empty() {
+        generate_mipmaps(&rgba, options.mip_levels)
+    } else {
+        mipmaps.into_iter().map(RgbaImage::into_raw).collect()
+    };
     let tb = apply_options(
         TextureBuilder::new(rgba.into_raw())
             .with_format(fmt)
             .with_channel_type(chan)
             .with_size(w as u16, h as u16),
         options,
-    );
+    )
+    .with_mip_data(mipmaps);
     renderer
         .create_texture(tb)
         .with_context(|_| error::Error::CreateTextureError)
@@ -542,6 +661,10 @@ pub enum TextureFormat {
     Bmp,
     /// Tga
     Tga,
+    /// Dds
+    Dds,
+    /// Hdr
+    Hdr,
 }
 
 impl SimpleFormat<Texture> for TextureFormat {
@@ -557,6 +680,8 @@ impl SimpleFormat<Texture> for TextureFormat {
             TextureFormat::Png => SimpleFormat::import(&PngFormat, bytes, options),
             TextureFormat::Bmp => SimpleFormat::import(&BmpFormat, bytes, options),
             TextureFormat::Tga => SimpleFormat::import(&TgaFormat, bytes, options),
+            TextureFormat::Dds => SimpleFormat::import(&DdsFormat, bytes, options),
+            TextureFormat::Hdr => SimpleFormat::import(&HdrFormat, bytes, options),
         }
     }
 }
@@ -585,7 +710,9 @@ mod serde_helper {
 
 #[cfg(test)]
 mod tests {
-    use super::TextureData;
+    use image::{Rgba, RgbaImage};
+
+    use super::{generate_mipmaps, premultiply_alpha, TextureData};
 
     #[test]
     fn texture_data_from_f32_3() {
@@ -596,6 +723,23 @@ mod tests {
             _ => panic!("Expected [f32; 3] to turn into TextureData::Rgba"),
         }
     }
+
+    #[test]
+    fn premultiplies_alpha() {
+        let mut image = RgbaImage::from_pixel(1, 1, Rgba([255, 128, 0, 128]));
+        premultiply_alpha(&mut image);
+        assert_eq!(image.into_raw(), vec![128, 64, 0, 128]);
+    }
+
+    #[test]
+    fn generates_mip_chain() {
+        let image = RgbaImage::new(8, 2);
+        let sizes = generate_mipmaps(&image, 8)
+            .iter()
+            .map(Vec::len)
+            .collect::<Vec<_>>();
+        assert_eq!(sizes, vec![16, 8, 4]);
+    }
 }
 
 serde_dyn::uuid! {
@@ -0,0 +1,374 @@
+//! Loading of MagicaVoxel (`.vox`) models.
+
+use serde::{Deserialize, Serialize};
+
+use amethyst_assets::SimpleFormat;
+use amethyst_core::nalgebra::Vector3;
+use amethyst_error::Error;
+
+use crate::{error, formats::MeshData, mesh::Mesh, vertex::PosColorNorm};
+
+const MAGIC: &[u8; 4] = b"VOX ";
+const CHUNK_HEADER_SIZE: usize = 12;
+// MagicaVoxel models are at most 256 voxels along each axis.
+const MAX_MODEL_SIZE: usize = 256;
+
+/// Allows loading meshes from MagicaVoxel files.
+///
+/// Only the first model of a file is loaded. Faces between neighbouring voxels are dropped and
+/// coplanar faces of the same color are merged into larger quads (greedy meshing), so the mesh
+/// stays small even for big models.
+///
+/// Each voxel is one unit in size. The model is centered on the origin in the horizontal plane
+/// and rests on `y = 0`; MagicaVoxel's Z (up) axis becomes the Y axis.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct VoxFormat;
+
+impl SimpleFormat<Mesh> for VoxFormat {
+    fn name() -> &'static str {
+        "VOX"
+    }
+
+    type Options = ();
+
+    fn import(&self, bytes: Vec<u8>, _: ()) -> Result<MeshData, Error> {
+        let model = VoxModel::parse(&bytes)?;
+        Ok(model.build_mesh().into())
+    }
+}
+
+fn invalid<T>(reason: &str) -> Result<T, Error> {
+    Err(Error::from(error::Error::InvalidVox(reason.to_owned())))
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Result<usize, Error> {
+    match data.get(offset..offset + 4) {
+        Some(b) => Ok(u32::from_le_bytes([b[0], b[1], b[2], b[3]]) as usize),
+        None => invalid("unexpected end of file"),
+    }
+}
+
+/// A single voxel model, with palette indices stored in a dense grid (`0` is empty).
+struct VoxModel {
+    size: [usize; 3],
+    grid: Vec<u8>,
+    palette: [[u8; 4]; 256],
+}
+
+impl VoxModel {
+    fn parse(data: &[u8]) -> Result<Self, Error> {
+        if data.len() < 8 || &data[0..4] != MAGIC {
+            return invalid("missing `VOX ` header");
+        }
+
+        let mut size = None;
+        let mut voxels = None;
+        let mut palette = None;
+
+        let mut offset = 8;
+        while offset < data.len() {
+            let id = match data.get(offset..offset + 4) {
+                Some(id) => id,
+                None => return invalid("unexpected end of file"),
+            };
+            let content_len = read_u32(data, offset + 4)?;
+            let children_len = read_u32(data, offset + 8)?;
+            let start = offset + CHUNK_HEADER_SIZE;
+            let content = match start
+                .checked_add(content_len)
+                .and_then(|end| data.get(start..end))
+            {
+                Some(content) => content,
+                None => return invalid("chunk exceeds the file size"),
+            };
+
+            match id {
+                b"SIZE" if size.is_none() => {
+                    let dimensions = [
+                        read_u32(content, 0)?,
+                        read_u32(content, 4)?,
+                        read_u32(content, 8)?,
+                    ];
+                    if dimensions.iter().any(|&d| d > MAX_MODEL_SIZE) {
+                        return invalid("model is larger than 256 voxels along an axis");
+                    }
+                    size = Some(dimensions);
+                }
+                b"XYZI" if voxels.is_none() => {
+                    let count = read_u32(content, 0)?;
+                    match count
+                        .checked_mul(4)
+                        .and_then(|len| len.checked_add(4))
+                        .and_then(|end| content.get(4..end))
+                    {
+                        Some(xyzi) => voxels = Some(xyzi),
+                        None => return invalid("voxel count exceeds the chunk size"),
+                    }
+                }
+                b"RGBA" => {
+                    if content.len() < 255 * 4 {
+                        return invalid("palette is too short");
+                    }
+                    // Palette index `i` is stored at position `i - 1`, index 0 is never used.
+                    let mut colors = [[0; 4]; 256];
+                    for (color, rgba) in colors[1..].iter_mut().zip(content.chunks(4)) {
+                        color.copy_from_slice(rgba);
+                    }
+                    palette = Some(colors);
+                }
+                _ => {}
+            }
+
+            // The children of `MAIN` are the model chunks, so step into it instead of over it.
+            offset = start + content_len;
+            if id != b"MAIN" {
+                offset = match offset.checked_add(children_len) {
+                    Some(offset) => offset,
+                    None => return invalid("chunk exceeds the file size"),
+                };
+            }
+        }
+
+        let size = match (size, voxels) {
+            (Some(size), Some(_)) => size,
+            _ => return invalid("the file contains no model"),
+        };
+        let len = match size[0]
+            .checked_mul(size[1])
+            .and_then(|len| len.checked_mul(size[2]))
+        {
+            Some(len) => len,
+            None => return invalid("model is too large"),
+        };
+        let mut grid = vec![0; len];
+        for xyzi in voxels.into_iter().flat_map(|v| v.chunks(4)) {
+            let (x, y, z) = (xyzi[0] as usize, xyzi[1] as usize, xyzi[2] as usize);
+            if x >= size[0] || y >= size[1] || z >= size[2] {
+                return invalid("voxel lies outside of the model");
+            }
+            grid[x + size[0] * (y + size[1] * z)] = xyzi[3];
+        }
+
+        Ok(VoxModel {
+            size,
+            grid,
+            palette: palette.unwrap_or_else(default_palette),
+        })
+    }
+
+    fn get(&self, pos: [isize; 3]) -> u8 {
+        let in_bounds = (0..3).all(|i| pos[i] >= 0 && (pos[i] as usize) < self.size[i]);
+        if in_bounds {
+            let [x, y, z] = [pos[0] as usize, pos[1] as usize, pos[2] as usize];
+            self.grid[x + self.size[0] * (y + self.size[1] * z)]
+        } else {
+            0
+        }
+    }
+
+    fn build_mesh(&self) -> Vec<PosColorNorm> {
+        let mut vertices = Vec::new();
+        for axis in 0..3 {
+            let (u, v) = ((axis + 1) % 3, (axis + 2) % 3);
+            let (width, height) = (self.size[u], self.size[v]);
+            let mut mask = vec![0u8; width * height];
+
+            for &dir in &[-1isize, 1] {
+                for slice in 0..self.size[axis] {
+                    // Collect the faces of this slice that point at an empty voxel.
+                    for b in 0..height {
+                        for a in 0..width {
+                            let mut pos = [0; 3];
+                            pos[axis] = slice as isize;
+                            pos[u] = a as isize;
+                            pos[v] = b as isize;
+                            let color = self.get(pos);
+                            pos[axis] += dir;
+                            mask[a + b * width] = if self.get(pos) == 0 { color } else { 0 };
+                        }
+                    }
+
+                    // Merge faces of the same color into rectangles.
+                    for b in 0..height {
+                        let mut a = 0;
+                        while a < width {
+                            let color = mask[a + b * width];
+                            if color == 0 {
+                                a += 1;
+                                continue;
+                            }
+                            let mut w = 1;
+                            while a + w < width && mask[a + w + b * width] == color {
+                                w += 1;
+                            }
+                            let mut h = 1;
+                            while b + h < height
+                                && (a..a + w).all(|i| mask[i + (b + h) * width] == color)
+                            {
+                                h += 1;
+                            }
+                            for row in b..b + h {
+                                for cell in &mut mask[a + row * width..a + w + row * width] {
+                                    *cell = 0;
+                                }
+                            }
+
+                            let depth = if dir > 0 { slice + 1 } else { slice };
+                            self.push_quad(
+                                &mut vertices,
+                                [axis, u, v],
+                                [depth, a, b],
+                                [w, h],
+                                dir,
+                                color,
+                            );
+                            a += w;
+                        }
+                    }
+                }
+            }
+        }
+        vertices
+    }
+
+    fn push_quad(
+        &self,
+        vertices: &mut Vec<PosColorNorm>,
+        [axis, u, v]: [usize; 3],
+        [depth, a, b]: [usize; 3],
+        [w, h]: [usize; 2],
+        dir: isize,
+        color: u8,
+    ) {
+        let corner = |da: usize, db: usize| {
+            let mut pos = [0.0; 3];
+            pos[axis] = depth as f32;
+            pos[u] = (a + da) as f32;
+            pos[v] = (b + db) as f32;
+            self.to_world(pos)
+        };
+        let mut normal = [0.0; 3];
+        normal[axis] = dir as f32;
+        let normal = self.to_world_dir(normal);
+        let rgba = self.palette[color as usize];
+        let color = [
+            f32::from(rgba[0]) / 255.0,
+            f32::from(rgba[1]) / 255.0,
+            f32::from(rgba[2]) / 255.0,
+            f32::from(rgba[3]) / 255.0,
+        ];
+
+        // `u x v` points along the positive axis, so the winding flips for negative faces.
+        let corners = [corner(0, 0), corner(w, 0), corner(w, h), corner(0, h)];
+        let order: [usize; 6] = if dir > 0 {
+            [0, 1, 2, 0, 2, 3]
+        } else {
+            [0, 2, 1, 0, 3, 2]
+        };
+        vertices.extend(order.iter().map(|&i| PosColorNorm {
+            position: corners[i],
+            color,
+            normal,
+        }));
+    }
+
+    /// Converts from the Z-up voxel grid to centered Y-up coordinates.
+    fn to_world(&self, pos: [f32; 3]) -> Vector3<f32> {
+        let x = pos[0] - self.size[0] as f32 / 2.0;
+        let y = pos[1] - self.size[1] as f32 / 2.0;
+        Vector3::new(x, pos[2], -y)
+    }
+
+    fn to_world_dir(&self, dir: [f32; 3]) -> Vector3<f32> {
+        Vector3::new(dir[0], dir[2], -dir[1])
+    }
+}
+
+/// The palette MagicaVoxel uses for files without an `RGBA` chunk: a 6x6x6 color cube followed
+/// by ramps of blue, green, red and gray.
+fn default_palette() -> [[u8; 4]; 256] {
+    const CUBE: [u8; 6] = [0xff, 0xcc, 0x99, 0x66, 0x33, 0x00];
+    const RAMP: [u8; 10] = [0xee, 0xdd, 0xbb, 0xaa, 0x88, 0x77, 0x55, 0x44, 0x22, 0x11];
+
+    let mut palette = [[0; 4]; 256];
+    let cube = CUBE
+        .iter()
+        .flat_map(|&r| {
+            CUBE.iter()
+                .flat_map(move |&g| CUBE.iter().map(move |&b| [r, g, b]))
+        })
+        // Black is part of the gray ramp instead.
+        .take(215);
+    let ramps = (0..4).flat_map(|ramp| {
+        RAMP.iter().map(move |&c| match ramp {
+            0 => [0, 0, c],
+            1 => [0, c, 0],
+            2 => [c, 0, 0],
+            _ => [c, c, c],
+        })
+    });
+    for (color, [r, g, b]) in palette[1..].iter_mut().zip(cube.chain(ramps)) {
+        *color = [r, g, b, 0xff];
+    }
+    palette
+}
+
+#[cfg(test)]
+mod tests {
+    use super::VoxModel;
+
+    fn chunk(id: &[u8; 4], content: &[u8], children: &[u8]) -> Vec<u8> {
+        let mut data = id.to_vec();
+        data.extend(&(content.len() as u32).to_le_bytes());
+        data.extend(&(children.len() as u32).to_le_bytes());
+        data.extend(content);
+        data.extend(children);
+        data
+    }
+
+    fn vox_file(size: [u32; 3], voxels: &[[u8; 4]]) -> Vec<u8> {
+        let size: Vec<u8> = size.iter().flat_map(|s| s.to_le_bytes().to_vec()).collect();
+        let mut xyzi = (voxels.len() as u32).to_le_bytes().to_vec();
+        for voxel in voxels {
+            xyzi.extend(voxel);
+        }
+        let mut children = chunk(b"SIZE", &size, &[]);
+        children.extend(chunk(b"XYZI", &xyzi, &[]));
+
+        let mut data = b"VOX ".to_vec();
+        data.extend(&150u32.to_le_bytes());
+        data.extend(chunk(b"MAIN", &[], &children));
+        data
+    }
+
+    #[test]
+    fn merges_faces_of_same_color() {
+        let data = vox_file([2, 1, 1], &[[0, 0, 0, 1], [1, 0, 0, 1]]);
+        let model = VoxModel::parse(&data).unwrap();
+        // One quad per side of the 2x1x1 box.
+        assert_eq!(model.build_mesh().len(), 6 * 6);
+    }
+
+    #[test]
+    fn keeps_faces_of_different_colors() {
+        let data = vox_file([2, 1, 1], &[[0, 0, 0, 1], [1, 0, 0, 2]]);
+        let model = VoxModel::parse(&data).unwrap();
+        // The four long sides are split by color, the inner faces are still hidden.
+        assert_eq!(model.build_mesh().len(), 10 * 6);
+    }
+
+    #[test]
+    fn rejects_oversized_models() {
+        let data = vox_file([257, 1, 1], &[[0, 0, 0, 1]]);
+        assert!(VoxModel::parse(&data).is_err());
+
+        let data = vox_file([u32::max_value(); 3], &[[0, 0, 0, 1]]);
+        assert!(VoxModel::parse(&data).is_err());
+    }
+
+    #[test]
+    fn rejects_voxels_outside_the_model() {
+        let data = vox_file([1, 1, 1], &[[1, 0, 0, 1]]);
+        assert!(VoxModel::parse(&data).is_err());
+    }
+}
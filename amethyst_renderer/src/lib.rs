@@ -35,9 +35,10 @@ pub use crate::{
     debug_drawing::{DebugLines, DebugLinesComponent},
     formats::{
         build_mesh_with_combo, create_mesh_asset, create_texture_asset, BmpFormat,
-        ComboMeshCreator, GraphicsPrefab, ImageData, JpgFormat, MaterialPrefab, MeshCreator,
-        MeshData, ObjFormat, PngFormat, SpriteRenderPrefab, SpriteSheetFormat, TextureData,
-        TextureFormat, TextureMetadata, TexturePrefab, TgaFormat,
+        ComboMeshCreator, DdsFormat, GraphicsPrefab, HdrFormat, ImageData, JpgFormat,
+        MaterialPrefab, MeshCreator, MeshData, ObjFormat, PngFormat, SpriteRenderPrefab,
+        SpriteSheetFormat, TextureData, TextureFormat, TextureMetadata, TexturePrefab, TgaFormat,
+        VoxFormat,
     },
     hidden::{Hidden, HiddenPropagate},
     hide_system::HideHierarchySystem,
@@ -45,7 +46,7 @@ pub use crate::{
         DeviceEvent, ElementState, Event, KeyboardInput, MouseButton, VirtualKeyCode, WindowEvent,
     },
     light::{DirectionalLight, Light, LightPrefab, PointLight, SpotLight, SunLight},
-    mesh::{vertex_data, Mesh, MeshBuilder, MeshHandle, VertexBuffer},
+    mesh::{vertex_data, Mesh, MeshBounds, MeshBuilder, MeshHandle, VertexBuffer},
     mtl::{Material, MaterialDefaults, TextureOffset},
     pass::{
         get_camera, set_vertex_args, DebugLinesParams, DrawDebugLines, DrawFlat, DrawFlat2D,
@@ -83,6 +84,9 @@ pub use crate::{
     visibility::{Visibility, VisibilitySortingSystem},
 };
 
+#[cfg(feature = "json")]
+pub use crate::formats::JsonSpriteSheetFormat;
+
 mod error;
 pub mod mouse;
 pub mod pipe;
@@ -127,12 +127,69 @@ where
 /// A handle to a mesh.
 pub type MeshHandle = Handle<Mesh>;
 
+/// Bounding volumes of a mesh, in the space of its vertex positions.
+///
+/// The mesh transform is not applied to the bounds.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub struct MeshBounds {
+    /// Minimum corner of the axis-aligned bounding box.
+    pub min: Vector3<f32>,
+    /// Maximum corner of the axis-aligned bounding box.
+    pub max: Vector3<f32>,
+    /// Center of the bounding sphere.
+    pub center: Vector3<f32>,
+    /// Radius of the bounding sphere.
+    pub radius: f32,
+}
+
+impl MeshBounds {
+    /// Computes the bounds enclosing all the given vertex positions.
+    ///
+    /// The bounding sphere is centered on the bounding box. An empty set of positions results in
+    /// zero-sized bounds at the origin.
+    pub fn from_positions<I>(positions: I) -> Self
+    where
+        I: IntoIterator<Item = Vector3<f32>>,
+        I::IntoIter: Clone,
+    {
+        let positions = positions.into_iter();
+        let mut iter = positions.clone();
+        let first = match iter.next() {
+            Some(first) => first,
+            None => {
+                return MeshBounds {
+                    min: Vector3::zeros(),
+                    max: Vector3::zeros(),
+                    center: Vector3::zeros(),
+                    radius: 0.0,
+                };
+            }
+        };
+        let (min, max) = iter.fold((first, first), |(min, max), pos| {
+            (min.zip_map(&pos, f32::min), max.zip_map(&pos, f32::max))
+        });
+        let center = (min + max) * 0.5;
+        let radius = positions
+            .map(|pos| (pos - center).norm_squared())
+            .fold(0.0, f32::max)
+            .sqrt();
+
+        MeshBounds {
+            min,
+            max,
+            center,
+            radius,
+        }
+    }
+}
+
 /// Represents a polygonal mesh.
 #[derive(Clone, Debug)]
 pub struct Mesh {
     slice: Slice,
     transform: Matrix4<f32>,
     vbufs: Vec<VertexBuffer>,
+    bounds: Option<MeshBounds>,
 }
 
 impl Mesh {
@@ -194,6 +251,17 @@ impl Mesh {
     pub fn transform(&self) -> Matrix4<f32> {
         self.transform
     }
+
+    /// Returns the bounding volumes of the mesh, if they are known.
+    ///
+    /// Meshes loaded as assets always have bounds.
+    pub fn bounds(&self) -> Option<&MeshBounds> {
+        self.bounds.as_ref()
+    }
+
+    pub(crate) fn set_bounds(&mut self, bounds: MeshBounds) {
+        self.bounds = Some(bounds);
+    }
 }
 
 /// Builds new meshes.
@@ -202,6 +270,8 @@ pub struct MeshBuilder<T> {
     prim: Primitive,
     transform: Matrix4<f32>,
     vertices: T,
+    #[serde(default)]
+    bounds: Option<MeshBounds>,
 }
 
 impl<D, V> MeshBuilder<((D, PhantomData<V>), ())>
@@ -216,6 +286,7 @@ where
             prim: Primitive::TriangleList,
             transform: Matrix4::identity(),
             vertices: (vertex_data(verts), ()),
+            bounds: None,
         }
     }
 }
@@ -235,6 +306,7 @@ where
             prim: self.prim,
             transform: self.transform,
             vertices: (vertex_data(verts), self.vertices),
+            bounds: self.bounds,
         }
     }
 
@@ -277,6 +349,14 @@ where
         self
     }
 
+    /// Sets the bounding volumes of the mesh.
+    ///
+    /// See `MeshBounds::from_positions` for computing them from the vertex positions.
+    pub fn with_bounds(mut self, bounds: MeshBounds) -> Self {
+        self.bounds = Some(bounds);
+        self
+    }
+
     /// Builds and returns the new mesh.
     pub fn build(self, fac: &mut Factory) -> Result<Mesh, Error> {
         use gfx::IndexBuffer;
@@ -294,6 +374,7 @@ where
             slice,
             transform: self.transform,
             vbufs: self.vertices.build(fac)?.collect(),
+            bounds: self.bounds,
         })
     }
 }
@@ -309,3 +390,34 @@ fn check_attributes_are_sorted(attrs: Attributes<'_>) -> bool {
     }
     true
 }
+
+#[cfg(test)]
+mod tests {
+    use amethyst_core::nalgebra::Vector3;
+
+    use super::MeshBounds;
+
+    #[test]
+    fn bounds_from_positions() {
+        let bounds = MeshBounds::from_positions(vec![
+            Vector3::new(-1.0, 0.0, 2.0),
+            Vector3::new(3.0, 2.0, -2.0),
+            Vector3::new(1.0, -2.0, 0.0),
+        ]);
+
+        assert_eq!(bounds.min, Vector3::new(-1.0, -2.0, -2.0));
+        assert_eq!(bounds.max, Vector3::new(3.0, 2.0, 2.0));
+        assert_eq!(bounds.center, Vector3::new(1.0, 0.0, 0.0));
+        assert_eq!(bounds.radius, 12.0f32.sqrt());
+    }
+
+    #[test]
+    fn bounds_from_no_positions() {
+        let bounds = MeshBounds::from_positions(Vec::<Vector3<f32>>::new());
+
+        assert_eq!(bounds.min, Vector3::zeros());
+        assert_eq!(bounds.max, Vector3::zeros());
+        assert_eq!(bounds.center, Vector3::zeros());
+        assert_eq!(bounds.radius, 0.0);
+    }
+}
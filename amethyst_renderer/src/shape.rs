@@ -102,6 +102,11 @@ pub enum Shape {
     Plane(Option<(usize, usize)>),
     /// Circle, located in the XY plane, number of points around the circle
     Circle(usize),
+    /// Capsule along the Z axis, made of two hemispheres of radius 1 joined by a cylinder of
+    /// the given height, number of points around the equator, number of points pole to pole.
+    /// An even number of points pole to pole is rounded up to the next odd number, so no ring
+    /// of points lies on the equator.
+    Capsule(f32, usize, usize),
 }
 
 /// `SystemData` needed to upload a `Shape` directly to create a `MeshHandle`
@@ -217,6 +222,19 @@ impl Shape {
                 scale,
             ),
             Shape::Circle(u) => generate_vertices(Circle::new(u), scale),
+            Shape::Capsule(height, u, v) => {
+                // Pull the hemispheres of a sphere apart, which stretches the band of faces
+                // crossing the equator into the cylinder. With an even `v` a ring would lie on
+                // the equator and end up on one side only, turning the cylinder into a cone.
+                let half_height = height / 2.;
+                generate_displaced_vertices(SphereUv::new(u, v | 1), scale, |pos| {
+                    if pos.z < 0. {
+                        Vector3::new(pos.x, pos.y, pos.z - half_height)
+                    } else {
+                        Vector3::new(pos.x, pos.y, pos.z + half_height)
+                    }
+                })
+            }
         };
         InternalShape(vertices)
     }
@@ -228,6 +246,22 @@ where
     F::Vertex: Clone + Copy + PartialEq,
     P: EmitTriangles<Vertex = usize>,
     G: SharedVertex<F::Vertex> + IndexedPolygon<P> + Iterator<Item = F>,
+{
+    generate_displaced_vertices(generator, scale, |pos| pos)
+}
+
+/// Like `generate_vertices`, but moves every position with `displace` before scaling it.
+fn generate_displaced_vertices<F, P, G, D>(
+    generator: G,
+    scale: Option<(f32, f32, f32)>,
+    displace: D,
+) -> Vec<VertexFormat>
+where
+    F: EmitTriangles<Vertex = Vertex>,
+    F::Vertex: Clone + Copy + PartialEq,
+    P: EmitTriangles<Vertex = usize>,
+    G: SharedVertex<F::Vertex> + IndexedPolygon<P> + Iterator<Item = F>,
+    D: Fn(Vector3<f32>) -> Vector3<f32>,
 {
     let vertices = generator.shared_vertex_iter().collect::<Vec<_>>();
     generator
@@ -236,9 +270,10 @@ where
         .map(|f| {
             f.map_vertex(|u| {
                 let v = vertices[u];
+                let pos = displace(Vector3::from(v.pos));
                 let pos = scale
-                    .map(|(x, y, z)| Vector3::new(v.pos.x * x, v.pos.y * y, v.pos.z * z))
-                    .unwrap_or_else(|| Vector3::from(v.pos));
+                    .map(|(x, y, z)| Vector3::new(pos.x * x, pos.y * y, pos.z * z))
+                    .unwrap_or(pos);
                 let normal = scale
                     .map(|(x, y, z)| {
                         Vector3::new(v.normal.x * x, v.normal.y * y, v.normal.z * z).normalize()
@@ -345,4 +380,39 @@ mod tests {
             Shape::Plane(None).generate::<Vec<PosNormTangTex>>(None)
         );
     }
+
+    #[test]
+    fn test_capsule() {
+        let vertices = Shape::Capsule(2., 16, 9).generate_vertices::<Vec<PosTex>>(None);
+        let (min, max) = vertices.iter().fold((0f32, 0f32), |(min, max), v| {
+            (min.min(v.position.z), max.max(v.position.z))
+        });
+        assert!((min + 2.).abs() < 1e-5);
+        assert!((max - 2.).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_capsule_cylinder_is_straight() {
+        for v in 8..12 {
+            let vertices = Shape::Capsule(2., 16, v).generate_vertices::<Vec<PosTex>>(None);
+            // The rings closest to the equator on each side bound the cylinder.
+            let ring = |upper: bool| {
+                vertices
+                    .iter()
+                    .filter(|vertex| (vertex.position.z > 0.) == upper)
+                    .min_by(|a, b| {
+                        let (a, b) = (a.position.z.abs(), b.position.z.abs());
+                        a.partial_cmp(&b).unwrap()
+                    })
+                    .map(|vertex| {
+                        let p = vertex.position;
+                        (p.z, (p.x * p.x + p.y * p.y).sqrt())
+                    })
+                    .unwrap()
+            };
+            let ((upper_z, upper_radius), (lower_z, lower_radius)) = (ring(true), ring(false));
+            assert!((upper_z + lower_z).abs() < 1e-5, "v = {}", v);
+            assert!((upper_radius - lower_radius).abs() < 1e-5, "v = {}", v);
+        }
+    }
 }
@@ -1,3 +1,5 @@
+use std::{collections::HashMap, ops::RangeInclusive};
+
 use serde::{Deserialize, Serialize};
 
 use amethyst_assets::{Asset, Handle, ProcessingState};
@@ -18,6 +20,19 @@ pub struct SpriteSheet {
     pub texture: Handle<Texture>,
     /// A list of sprites in this sprite sheet.
     pub sprites: Vec<Sprite>,
+    /// Named ranges of sprite numbers, for example the animations tagged in an Aseprite file.
+    pub ranges: HashMap<String, RangeInclusive<usize>>,
+}
+
+impl SpriteSheet {
+    /// Creates a sprite sheet without named ranges.
+    pub fn new(texture: Handle<Texture>, sprites: Vec<Sprite>) -> Self {
+        SpriteSheet {
+            texture,
+            sprites,
+            ranges: HashMap::new(),
+        }
+    }
 }
 
 impl Asset for SpriteSheet {
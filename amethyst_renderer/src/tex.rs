@@ -71,6 +71,7 @@ pub struct TextureBuilder<D, T> {
     info: Info,
     channel_type: ChannelType,
     sampler: SamplerInfo,
+    mipmaps: Vec<D>,
     pd: PhantomData<T>,
 }
 
@@ -113,6 +114,7 @@ where
             },
             channel_type: ChannelFormat::get_channel_type(),
             sampler: SamplerInfo::new(FilterMethod::Scale, WrapMode::Clamp),
+            mipmaps: Vec::new(),
             pd: PhantomData,
         }
     }
@@ -125,12 +127,23 @@ where
 
     /// Sets the number of mipmap levels to generate.
     ///
-    /// FIXME: Only encoders can generate mipmap levels.
+    /// FIXME: Only encoders can generate mipmap levels, use `with_mip_data` to provide them.
     pub fn mip_levels(mut self, val: u8) -> Self {
         self.info.levels = val;
         self
     }
 
+    /// Sets the texel data of the mipmap levels following the base level, ordered from the
+    /// largest to the smallest level. Each level has half the width and height of the previous
+    /// one (rounded down, but at least one texel).
+    ///
+    /// This also sets the number of mipmap levels accordingly.
+    pub fn with_mip_data(mut self, levels: Vec<D>) -> Self {
+        self.info.levels = levels.len() as u8 + 1;
+        self.mipmaps = levels;
+        self
+    }
+
     /// Sets the texture width and height in pixels.
     pub fn with_size(mut self, w: u16, h: u16) -> Self {
         use gfx::texture::{AaMode, Kind};
@@ -159,43 +172,41 @@ where
 
     /// Builds and returns the new texture.
     pub fn build(self, fac: &mut Factory) -> Result<Texture, Error> {
-        use std::mem::size_of;
+        use std::{iter::once, mem::size_of};
 
         use gfx::{format::Swizzle, memory::cast_slice, texture::ResourceDesc, Factory};
 
         // This variable has to live here to make sure the flipped
-        // buffer lives long enough. (If one exists)
-        let mut v_flip_buffer;
-        let mut data = self.data.as_ref();
+        // buffers live long enough. (If they exist)
+        let v_flip_buffers;
+
+        let mut levels = once(self.data.as_ref())
+            .chain(self.mipmaps.iter().map(|data| data.as_ref()))
+            .collect::<Vec<&[T]>>();
 
         if cfg!(feature = "opengl") {
             let pixel_width = (self.info.format.get_total_bits() / 8) as usize / size_of::<T>();
-            v_flip_buffer = Vec::with_capacity(data.len());
             let (w, h, _, _) = self.info.kind.get_dimensions();
-            let w = w as usize;
-            let h = h as usize;
-            if w * h * pixel_width != data.len() {
-                let error = format!(
-                    "Texture size mismatch: Expected pixel data vector of length {:?} (actual: {:?})",
-                    w * h * pixel_width,
-                    data.len()
-                );
-                return Err(error::Error::PixelDataMismatch(error).into());
-            }
-            for y in 0..h {
-                for x in 0..(w * pixel_width) {
-                    v_flip_buffer.push(data[x + (h - y - 1) * w * pixel_width]);
-                    // Uncomment this if you need to debug this.
-                    // println!("x: {}, y: {}, w: {}, h: {}, pw: {}", x, y, w, h, pixel_width);
-                }
-            }
-            data = &v_flip_buffer;
+            v_flip_buffers = levels
+                .iter()
+                .enumerate()
+                .map(|(level, data)| {
+                    let w = ((w as usize) >> level).max(1);
+                    let h = ((h as usize) >> level).max(1);
+                    flip_vertically(data, w, h, pixel_width)
+                })
+                .collect::<Result<Vec<_>, Error>>()?;
+            levels = v_flip_buffers.iter().map(Vec::as_slice).collect();
         }
 
+        let raw_levels = levels
+            .iter()
+            .map(|data| cast_slice(data))
+            .collect::<Vec<&[u8]>>();
         let tex = fac.create_texture_raw(
             self.info,
             Some(self.channel_type),
-            Some((&[cast_slice(data)], Mipmap::Provided)),
+            Some((&raw_levels, Mipmap::Provided)),
         )?;
 
         let desc = ResourceDesc {
@@ -216,3 +227,24 @@ where
         })
     }
 }
+
+fn flip_vertically<T: Copy>(
+    data: &[T],
+    w: usize,
+    h: usize,
+    pixel_width: usize,
+) -> Result<Vec<T>, Error> {
+    if w * h * pixel_width != data.len() {
+        let error = format!(
+            "Texture size mismatch: Expected pixel data vector of length {:?} (actual: {:?})",
+            w * h * pixel_width,
+            data.len()
+        );
+        return Err(error::Error::PixelDataMismatch(error).into());
+    }
+    let mut flipped = Vec::with_capacity(data.len());
+    for y in (0..h).rev() {
+        flipped.extend_from_slice(&data[y * w * pixel_width..(y + 1) * w * pixel_width]);
+    }
+    Ok(flipped)
+}
@@ -89,14 +89,14 @@ impl SpriteRenderAnimationFixture {
     }
 
     fn sprite_sheet(texture: Handle<Texture>) -> SpriteSheet {
-        SpriteSheet {
+        SpriteSheet::new(
             texture,
-            sprites: vec![Sprite {
+            vec![Sprite {
                 width: 10.0,
                 height: 10.0,
                 offsets: [5.; 2],
                 tex_coords: [0.0, 1.0, 0.0, 1.0].into(),
             }],
-        }
+        )
     }
 }
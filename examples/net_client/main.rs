@@ -9,6 +9,8 @@ use amethyst::{
 use log::info;
 use std::time::Duration;
 
+/// Sends messages to the net_server example. Start both examples within ten seconds of each
+/// other, otherwise the connection times out before the handshake completes.
 fn main() -> Result<()> {
     amethyst::start_logger(Default::default());
 
@@ -42,8 +44,9 @@ impl SimpleState for State1 {
     }
 }
 
-/// A simple system that sends a ton of messages to all connections.
-/// In this case, only the server is connected.
+/// A simple system that sends a ton of messages to all connected connections.
+/// In this case, only the server is connected. Messages written before the handshake
+/// completed would be dropped by the server.
 struct SpamSystem {}
 
 impl SpamSystem {
@@ -56,6 +59,9 @@ impl<'a> System<'a> for SpamSystem {
     type SystemData = (WriteStorage<'a, NetConnection<()>>, Read<'a, Time>);
     fn run(&mut self, (mut connections, time): Self::SystemData) {
         for conn in (&mut connections).join() {
+            if conn.state != ConnectionState::Connected {
+                continue;
+            }
             info!("Sending 10k messages.");
             for i in 0..10000 {
                 let ev = NetEvent::TextMessage {
@@ -11,6 +11,8 @@ use log::info;
 
 use std::time::Duration;
 
+/// Receives the messages sent by the net_client example. Start both examples within ten seconds
+/// of each other, otherwise the connection times out before the handshake completes.
 fn main() -> Result<()> {
     amethyst::start_logger(Default::default());
 
@@ -68,6 +70,10 @@ impl<'a> System<'a> for SpamReceiveSystem {
                 count += 1;
                 match ev {
                     &NetEvent::TextMessage { ref msg } => info!("{}", msg),
+                    &NetEvent::Connect { .. } | &NetEvent::Connected { .. } => {
+                        info!("Client connected")
+                    }
+                    &NetEvent::Disconnected { ref reason } => info!("Disconnected: {}", reason),
                     _ => {}
                 }
             }
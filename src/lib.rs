@@ -86,6 +86,7 @@ pub use self::{
     callback_queue::{Callback, CallbackQueue},
     error::Error,
     game_data::{DataInit, GameData, GameDataBuilder},
+    loading_state::{LoadingState, NextState},
     logger::{start_logger, LevelFilter as LogLevelFilter, Logger, LoggerConfig, StdoutLog},
     state::{
         EmptyState, EmptyTrans, SimpleState, SimpleTrans, State, StateData, StateMachine, Trans,
@@ -102,6 +103,7 @@ pub mod prelude;
 mod app;
 mod callback_queue;
 mod game_data;
+mod loading_state;
 mod logger;
 mod state;
 mod state_event;
@@ -0,0 +1,262 @@
+//! A state that waits for assets to finish loading.
+
+use log::error;
+
+use crate::{
+    assets::{Completion, ProgressCounter},
+    ecs::prelude::World,
+    GameData, SimpleState, SimpleTrans, State, StateData, StateEvent, Trans,
+};
+
+/// The state that is switched to once loading is complete.
+pub type NextState = Box<dyn State<GameData<'static, 'static>, StateEvent>>;
+
+/// A `State` that starts loading assets and switches to another state once all of them are
+/// loaded.
+///
+/// The assets are loaded by the function given to `LoadingState::new`, which is called once
+/// when the state starts and has to pass the `ProgressCounter` to every load. Because the
+/// `Processor`s of the loaded assets only finish an asset once everything it depends on is
+/// loaded, for example the textures of a prefab, the counter is complete only when all of them
+/// are ready to use.
+///
+/// If any asset fails to load, the function given to `LoadingState::with_failure` decides what
+/// happens next. Without one, the errors are logged and the application quits.
+///
+/// ## Example
+///
+/// ```rust,no_run
+/// use amethyst::{
+///     assets::{AssetStorage, Handle, Loader},
+///     prelude::*,
+///     renderer::{PngFormat, Texture, TextureMetadata},
+///     LoadingState,
+/// };
+///
+/// struct LogoTexture(Handle<Texture>);
+///
+/// struct MainMenu;
+///
+/// impl SimpleState for MainMenu {}
+///
+/// let state = LoadingState::new(
+///     |world, progress| {
+///         let handle = world.read_resource::<Loader>().load(
+///             "texture/logo.png",
+///             PngFormat,
+///             TextureMetadata::srgb(),
+///             progress,
+///             &world.read_resource::<AssetStorage<Texture>>(),
+///         );
+///         world.add_resource(LogoTexture(handle));
+///     },
+///     Box::new(MainMenu),
+/// )
+/// .with_progress(|progress, _| {
+///     println!("{}/{}", progress.num_finished(), progress.num_assets());
+/// })
+/// .with_failure(|progress, _| {
+///     for e in progress.errors() {
+///         eprintln!("Failed to load {}: {}", e.asset_name, e.error);
+///     }
+///     Trans::Quit
+/// });
+/// ```
+pub struct LoadingState {
+    load: Option<Box<dyn FnOnce(&mut World, &mut ProgressCounter)>>,
+    on_progress: Option<Box<dyn FnMut(&ProgressCounter, &mut World)>>,
+    on_failure: Option<Box<dyn FnMut(&ProgressCounter, &mut World) -> SimpleTrans>>,
+    progress: ProgressCounter,
+    next: Option<NextState>,
+}
+
+impl LoadingState {
+    /// Creates a new `LoadingState`, which calls `load` when it starts and switches to `next`
+    /// once everything it loaded is ready.
+    pub fn new<F>(load: F, next: NextState) -> Self
+    where
+        F: FnOnce(&mut World, &mut ProgressCounter) + 'static,
+    {
+        LoadingState {
+            load: Some(Box::new(load)),
+            on_progress: None,
+            on_failure: None,
+            progress: ProgressCounter::new(),
+            next: Some(next),
+        }
+    }
+
+    /// Sets a function that is called with the loading progress every frame, for example to
+    /// update a loading bar.
+    pub fn with_progress<F>(mut self, on_progress: F) -> Self
+    where
+        F: FnMut(&ProgressCounter, &mut World) + 'static,
+    {
+        self.on_progress = Some(Box::new(on_progress));
+        self
+    }
+
+    /// Sets a function that is called every frame once an asset failed to load, instead of
+    /// quitting. The errors can be taken from the `ProgressCounter`, and the returned transition
+    /// is applied, for example to switch to an error screen or to continue without the assets.
+    pub fn with_failure<F>(mut self, on_failure: F) -> Self
+    where
+        F: FnMut(&ProgressCounter, &mut World) -> SimpleTrans + 'static,
+    {
+        self.on_failure = Some(Box::new(on_failure));
+        self
+    }
+
+    /// Returns the progress of the assets loaded so far.
+    pub fn progress(&self) -> &ProgressCounter {
+        &self.progress
+    }
+}
+
+impl SimpleState for LoadingState {
+    fn on_start(&mut self, data: StateData<'_, GameData<'_, '_>>) {
+        if let Some(load) = self.load.take() {
+            load(data.world, &mut self.progress);
+        }
+    }
+
+    fn update(&mut self, data: &mut StateData<'_, GameData<'_, '_>>) -> SimpleTrans {
+        if let Some(ref mut on_progress) = self.on_progress {
+            on_progress(&self.progress, data.world);
+        }
+
+        match self.progress.complete() {
+            Completion::Loading => Trans::None,
+            Completion::Complete => self.switch_to_next(),
+            Completion::Failed => match self.on_failure {
+                Some(ref mut on_failure) => on_failure(&self.progress, data.world),
+                None => {
+                    for e in self.progress.errors() {
+                        error!(
+                            "Failed to load asset {} of type {}: {}",
+                            e.asset_name, e.asset_type_name, e.error
+                        );
+                    }
+                    Trans::Quit
+                }
+            },
+        }
+    }
+}
+
+impl LoadingState {
+    fn switch_to_next(&mut self) -> SimpleTrans {
+        match self.next.take() {
+            Some(next) => Trans::Switch(next),
+            None => Trans::Pop,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc};
+
+    use crate::{
+        assets::{Progress, ProgressCounter, Tracker},
+        ecs::prelude::{DispatcherBuilder, World},
+        error::Error,
+        GameData, SimpleState, SimpleTrans, StateData, Trans,
+    };
+
+    use super::LoadingState;
+
+    struct Next;
+
+    impl SimpleState for Next {}
+
+    type Trackers = Rc<RefCell<Vec<Box<dyn Tracker>>>>;
+
+    // Creates a state loading two assets, whose trackers are returned to finish them by hand.
+    fn loading_state() -> (LoadingState, Trackers) {
+        let trackers = Trackers::default();
+        let shared = trackers.clone();
+        let state = LoadingState::new(
+            move |_, progress: &mut ProgressCounter| {
+                for _ in 0..2 {
+                    let mut progress = &mut *progress;
+                    progress.add_assets(1);
+                    shared
+                        .borrow_mut()
+                        .push(Box::new(progress.create_tracker()));
+                }
+            },
+            Box::new(Next),
+        );
+        (state, trackers)
+    }
+
+    fn update(state: &mut LoadingState, world: &mut World) -> SimpleTrans {
+        let mut game_data = GameData::new(DispatcherBuilder::new().build());
+        state.update(&mut StateData::new(world, &mut game_data))
+    }
+
+    fn start(state: &mut LoadingState, world: &mut World) {
+        let mut game_data = GameData::new(DispatcherBuilder::new().build());
+        state.on_start(StateData::new(world, &mut game_data));
+    }
+
+    #[test]
+    fn switches_to_next_state_once_complete() {
+        let mut world = World::new();
+        let (mut state, trackers) = loading_state();
+        start(&mut state, &mut world);
+        assert_eq!(state.progress().num_assets(), 2);
+
+        let first = trackers.borrow_mut().pop().unwrap();
+        first.success();
+        match update(&mut state, &mut world) {
+            Trans::None => {}
+            _ => panic!("Expected to keep loading"),
+        }
+
+        let second = trackers.borrow_mut().pop().unwrap();
+        second.success();
+        match update(&mut state, &mut world) {
+            Trans::Switch(_) => {}
+            _ => panic!("Expected to switch to the next state"),
+        }
+    }
+
+    #[test]
+    fn failure_is_passed_to_the_failure_function() {
+        let mut world = World::new();
+        let (state, trackers) = loading_state();
+        let failures = Rc::new(RefCell::new(Vec::new()));
+        let shared = failures.clone();
+        let mut state = state.with_failure(move |progress, _| {
+            shared
+                .borrow_mut()
+                .extend(progress.errors().into_iter().map(|e| e.asset_name));
+            Trans::Pop
+        });
+        start(&mut state, &mut world);
+
+        let tracker = trackers.borrow_mut().pop().unwrap();
+        tracker.fail(0, "Test", "broken".to_owned(), Error::from_string("Broken"));
+        match update(&mut state, &mut world) {
+            Trans::Pop => {}
+            _ => panic!("Expected the transition of the failure function"),
+        }
+        assert_eq!(*failures.borrow(), vec!["broken".to_owned()]);
+    }
+
+    #[test]
+    fn failure_without_failure_function_quits() {
+        let mut world = World::new();
+        let (mut state, trackers) = loading_state();
+        start(&mut state, &mut world);
+
+        let tracker = trackers.borrow_mut().pop().unwrap();
+        tracker.fail(0, "Test", "broken".to_owned(), Error::from_string("Broken"));
+        match update(&mut state, &mut world) {
+            Trans::Quit => {}
+            _ => panic!("Expected to quit"),
+        }
+    }
+}